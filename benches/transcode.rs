@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use mutf8::__bench::{ascii_run, ascii_run_bytes, ascii_run_word};
+use mutf8::{decode, encode, mutf8_to_utf8};
+
+/// Roughly what the strings in a class file's constant pool look like.
+/// Mostly ascii identifiers and descriptors, with the odd string literal.
+fn class_file_strings() -> Vec<String> {
+	let mut strings = vec![];
+	for i in 0..1000 {
+		strings.push(format!("com/example/project/module{}/SomeService$Inner{}", i % 17, i));
+		strings.push(format!("(Ljava/lang/String;ILjava/util/List<Ljava/lang/Integer;>;)V{}", i));
+		strings.push(format!("method{}", i));
+		if i % 10 == 0 {
+			strings.push(format!("Grüße, {} \u{1F600} mit \0 drin", i));
+		}
+	}
+	strings
+}
+
+fn bench_class_file(c: &mut Criterion) {
+	let utf8 = class_file_strings();
+	let mutf8: Vec<Vec<u8>> = utf8.iter()
+		.map(|s| encode(s).into_owned())
+		.collect();
+	let bytes = utf8.iter().map(|s| s.len()).sum::<usize>() as u64;
+
+	let mut group = c.benchmark_group("class_file");
+	group.throughput(Throughput::Bytes(bytes));
+	group.bench_function("encode", |b| b.iter(|| {
+		for s in &utf8 {
+			black_box(encode(black_box(s)));
+		}
+	}));
+	group.bench_function("mutf8_to_utf8", |b| b.iter(|| {
+		for s in &mutf8 {
+			black_box(mutf8_to_utf8(black_box(s)).unwrap());
+		}
+	}));
+	group.bench_function("decode", |b| b.iter(|| {
+		for s in &mutf8 {
+			black_box(decode(black_box(s)).unwrap());
+		}
+	}));
+	group.finish();
+}
+
+fn bench_long_ascii(c: &mut Criterion) {
+	let utf8 = "java/lang/invoke/MethodHandles$Lookup ".repeat(1000);
+
+	let mut group = c.benchmark_group("long_ascii");
+	group.throughput(Throughput::Bytes(utf8.len() as u64));
+	group.bench_function("encode", |b| b.iter(|| black_box(encode(black_box(&utf8)))));
+	group.bench_function("decode", |b| b.iter(|| black_box(decode(black_box(utf8.as_bytes())).unwrap())));
+	group.finish();
+}
+
+/// Compares skipping over ascii a byte at a time, a word at a time, and with SIMD, when it's available.
+fn bench_ascii_run(c: &mut Criterion) {
+	let utf8 = "java/lang/invoke/MethodHandles$Lookup ".repeat(1000);
+	let input = utf8.as_bytes();
+
+	let mut group = c.benchmark_group("ascii_run");
+	group.throughput(Throughput::Bytes(input.len() as u64));
+	group.bench_function("bytes", |b| b.iter(|| black_box(ascii_run_bytes(black_box(input)))));
+	group.bench_function("word", |b| b.iter(|| black_box(ascii_run_word(black_box(input)))));
+	group.bench_function("detected", |b| b.iter(|| black_box(ascii_run(black_box(input)))));
+	group.finish();
+}
+
+criterion_group!(benches, bench_class_file, bench_long_ascii, bench_ascii_run);
+criterion_main!(benches);
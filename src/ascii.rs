@@ -0,0 +1,103 @@
+//! Finding runs of ASCII, which are encoded the same way in UTF-8 and MUTF-8.
+//!
+//! Nul is the one exception, so it's never counted as part of a run.
+
+const WORD: usize = std::mem::size_of::<usize>();
+const LOW: usize = usize::MAX / 0xFF;
+const HIGH: usize = LOW << 7;
+
+/// Returns whether the byte is ASCII, excluding nul.
+#[inline]
+pub(crate) fn is_ascii(byte: u8) -> bool {
+	byte.wrapping_sub(1) < 0x7F
+}
+
+/// Returns the number of ASCII bytes, excluding nul, at the start of the input.
+#[inline]
+pub fn ascii_run(input: &[u8]) -> usize {
+	#[cfg(target_arch = "x86_64")]
+	{
+		if input.len() >= 32 && is_x86_feature_detected!("avx2") {
+			// safety: AVX2 support was checked above.
+			return unsafe { x86_64::ascii_run_avx2(input) };
+		}
+		if input.len() >= 16 {
+			// safety: SSE2 is part of the x86_64 baseline.
+			return unsafe { x86_64::ascii_run_sse2(input) };
+		}
+	}
+
+	ascii_run_word(input)
+}
+
+/// Processes a word at a time, and finishes up a byte at a time.
+#[inline]
+pub fn ascii_run_word(input: &[u8]) -> usize {
+	let mut i = 0;
+	for chunk in input.chunks_exact(WORD) {
+		let mut bytes = [0; WORD];
+		bytes.copy_from_slice(chunk);
+		let word = usize::from_ne_bytes(bytes);
+
+		// The high bit of a byte is set if it's non-ascii, or if it's nul.
+		let zero = word.wrapping_sub(LOW) & !word & HIGH;
+		if (word & HIGH) | zero != 0 {
+			break;
+		}
+		i += WORD;
+	}
+	i + ascii_run_bytes(&input[i..])
+}
+
+/// Processes a byte at a time.
+#[inline]
+pub fn ascii_run_bytes(input: &[u8]) -> usize {
+	input.iter()
+		.position(|&byte| byte == 0 || byte >= 0x80)
+		.unwrap_or(input.len())
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+	use std::arch::x86_64::*;
+
+	use super::ascii_run_word;
+
+	#[target_feature(enable = "sse2")]
+	pub(super) unsafe fn ascii_run_sse2(input: &[u8]) -> usize {
+		let len = input.len();
+		let ptr = input.as_ptr();
+		let zero = _mm_setzero_si128();
+
+		let mut i = 0;
+		while i + 16 <= len {
+			let chunk = _mm_loadu_si128(ptr.add(i) as *const __m128i);
+			// The high bit of a byte is set if it's non-ascii, or if it's nul.
+			let mask = _mm_movemask_epi8(_mm_or_si128(chunk, _mm_cmpeq_epi8(chunk, zero)));
+			if mask != 0 {
+				return i + mask.trailing_zeros() as usize;
+			}
+			i += 16;
+		}
+		i + ascii_run_word(&input[i..])
+	}
+
+	#[target_feature(enable = "avx2")]
+	pub(super) unsafe fn ascii_run_avx2(input: &[u8]) -> usize {
+		let len = input.len();
+		let ptr = input.as_ptr();
+		let zero = _mm256_setzero_si256();
+
+		let mut i = 0;
+		while i + 32 <= len {
+			let chunk = _mm256_loadu_si256(ptr.add(i) as *const __m256i);
+			// The high bit of a byte is set if it's non-ascii, or if it's nul.
+			let mask = _mm256_movemask_epi8(_mm256_or_si256(chunk, _mm256_cmpeq_epi8(chunk, zero)));
+			if mask != 0 {
+				return i + mask.trailing_zeros() as usize;
+			}
+			i += 32;
+		}
+		i + ascii_run_sse2(&input[i..])
+	}
+}
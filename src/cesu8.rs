@@ -0,0 +1,25 @@
+use std::borrow::Cow;
+
+use crate::error::Result;
+use crate::mutf8::{decode_with, encode_with};
+use crate::options::{DecodeOptions, EncodeOptions};
+
+/// Converts CESU-8 into UTF-8.
+///
+/// CESU-8 is MUTF-8 without the special encoding for nul.
+/// The input is strictly validated, see `DecodeOptions::cesu8` for the exact rules.
+///
+/// The input is borrowed if it doesn't contain any surrogate pairs.
+pub fn cesu8_to_utf8(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+	decode_with(input, &DecodeOptions::cesu8())
+}
+
+/// Converts UTF-8 bytes into CESU-8.
+///
+/// The input is validated first, so an error is returned for anything that isn't valid UTF-8.
+///
+/// The input is borrowed if it doesn't contain any supplementary characters.
+pub fn utf8_to_cesu8(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+	let input = std::str::from_utf8(input)?;
+	Ok(encode_with(input, &EncodeOptions::cesu8()))
+}
@@ -0,0 +1,106 @@
+//! Splitting MUTF-8 input into valid and invalid parts, like `std::str::Utf8Chunks`.
+
+use std::borrow::Cow;
+use std::iter::FusedIterator;
+
+use crate::decode;
+
+/// A valid part of the input, followed by the invalid bytes that ended it.
+///
+/// Created by `Chunks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk<'a> {
+	valid: Cow<'a, str>,
+	invalid: &'a [u8],
+}
+
+impl<'a> Chunk<'a> {
+	/// Returns the valid part, decoded into UTF-8.
+	///
+	/// This is borrowed from the input if it's encoded the same way as UTF-8.
+	#[inline]
+	pub fn valid(&self) -> &Cow<'a, str> {
+		&self.valid
+	}
+
+	/// Returns the invalid bytes that follow the valid part.
+	///
+	/// This is empty for the last chunk, if the input ends with valid data.
+	/// Otherwise, it's a single invalid sequence, the same bytes `Error::error_len` covers, or the rest of the input if it ended partway through a sequence.
+	#[inline]
+	pub fn invalid(&self) -> &'a [u8] {
+		self.invalid
+	}
+
+	/// Splits the chunk into its valid and invalid parts.
+	#[inline]
+	pub fn into_parts(self) -> (Cow<'a, str>, &'a [u8]) {
+		(self.valid, self.invalid)
+	}
+}
+
+/// An iterator over the chunks of MUTF-8 input.
+///
+/// Each chunk holds as much valid input as possible, followed by the sequence that couldn't be decoded.
+/// Sequences are decoded by the same strict rules as `mutf8_to_utf8`, so lone surrogates end up in the invalid part as well.
+///
+/// Created by `chunks` or `mstr::chunks`.
+#[derive(Debug, Clone)]
+pub struct Chunks<'a> {
+	input: &'a [u8],
+}
+
+/// Returns an iterator over the valid and invalid chunks of the input.
+///
+/// ```
+/// use mutf8::chunks;
+///
+/// let mut output = String::new();
+/// for chunk in chunks(b"a\xC0\x80b\xFFc") {
+///     output.push_str(chunk.valid());
+///     if !chunk.invalid().is_empty() {
+///         output.push_str(&format!("[{:02X?}]", chunk.invalid()));
+///     }
+/// }
+/// assert_eq!(output, "a\0b[[FF]]c");
+/// ```
+pub fn chunks(input: &[u8]) -> Chunks<'_> {
+	Chunks {
+		input,
+	}
+}
+
+impl<'a> Iterator for Chunks<'a> {
+	type Item = Chunk<'a>;
+
+	fn next(&mut self) -> Option<Chunk<'a>> {
+		let input = self.input;
+		if input.is_empty() {
+			return None;
+		}
+
+		let chunk = match decode(input) {
+			Ok(valid) => {
+				self.input = &[];
+				Chunk {
+					valid,
+					invalid: &[],
+				}
+			}
+			Err(error) => {
+				let (valid, rest) = input.split_at(error.valid_up_to());
+				let (invalid, rest) = rest.split_at(error.error_len().unwrap_or(rest.len()));
+				self.input = rest;
+				Chunk {
+					valid: decode(valid).expect("input is valid up to the error"),
+					invalid,
+				}
+			}
+		};
+
+		Some(chunk)
+	}
+}
+
+impl FusedIterator for Chunks<'_> {
+}
@@ -0,0 +1,178 @@
+//! Decoding MUTF-8 that arrives in chunks.
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::mutf8::{decode_to, next_unit, Unit, MODE_COPY};
+use crate::options::DecodeOptions;
+
+/// The most bytes that are held back between chunks, the high half of a surrogate pair followed by two bytes of the low half.
+const MAX_PENDING: usize = 5;
+
+/// The longest sequence, a surrogate pair.
+const MAX_SEQUENCE: usize = 6;
+
+/// An incremental MUTF-8 decoder.
+///
+/// Input can be split anywhere, even in the middle of a sequence or between the two halves of a surrogate pair.
+/// Anything incomplete at the end of a chunk is held back until the next one, or reported by `finish` if there's none.
+/// Sequences are decoded by the same strict rules as `mutf8_to_utf8`.
+///
+/// ```
+/// use mutf8::Decoder;
+///
+/// let mut decoder = Decoder::new();
+/// let mut output = String::new();
+/// decoder.decode_chunk(b"a\xC0", &mut output).unwrap();
+/// decoder.decode_chunk(b"\x80\xED\xA0\xBD\xED", &mut output).unwrap();
+/// decoder.decode_chunk(b"\xB8\x80", &mut output).unwrap();
+/// decoder.finish().unwrap();
+/// assert_eq!(output, "a\0\u{1F600}");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Decoder {
+	pending: [u8; MAX_PENDING],
+	pending_len: usize,
+	position: usize,
+}
+
+impl Decoder {
+	/// Creates a decoder, at the start of the input.
+	pub const fn new() -> Self {
+		Decoder {
+			pending: [0; MAX_PENDING],
+			pending_len: 0,
+			position: 0,
+		}
+	}
+
+	/// Decodes the next chunk of input, appending it to `output`.
+	///
+	/// Returns the number of bytes appended.
+	/// An error holds the offset into the whole input, not just this chunk.
+	/// Everything before the error is still appended, but the decoder shouldn't be used any further.
+	pub fn decode_chunk(&mut self, input: &[u8], output: &mut String) -> Result<usize> {
+		let start = output.len();
+		let mut input = input;
+
+		if self.pending_len > 0 {
+			let pending = self.pending_len;
+			let extra = input.len().min(MAX_SEQUENCE);
+
+			let mut buf = [0; MAX_PENDING + MAX_SEQUENCE];
+			buf[..pending].copy_from_slice(&self.pending[..pending]);
+			buf[pending..pending + extra].copy_from_slice(&input[..extra]);
+			let buf = &buf[..pending + extra];
+			self.pending_len = 0;
+
+			if extra == input.len() {
+				// The whole chunk fits alongside the pending bytes.
+				self.decode_buffer(buf, output)?;
+				return Ok(output.len() - start);
+			}
+
+			// Only the sequences that start in the pending bytes are decoded here.
+			// Each of them is complete, as there's always a whole sequence worth of input after them.
+			let mut end = 0;
+			while end < pending {
+				end += match next_unit(buf, end, &DecodeOptions::new()) {
+					Ok(Unit::Same(width)) | Ok(Unit::Char(_, width)) => width,
+					Ok(Unit::Surrogate) => 3,
+					Err(_) => {
+						// Let the decoder report it, after decoding everything before it.
+						end = buf.len();
+						break;
+					}
+				};
+			}
+			self.decode(&buf[..end], output)?;
+			input = &input[end - pending..];
+		}
+
+		self.decode_buffer(input, output)?;
+		Ok(output.len() - start)
+	}
+
+	/// Checks that the input didn't end in the middle of a sequence.
+	///
+	/// If it did, the error is the same one `mutf8_to_utf8` returns for the whole input.
+	/// The decoder can then be reused, as if it's at the start of the input.
+	pub fn finish(&mut self) -> Result<()> {
+		let pending = self.pending_len;
+		let position = self.position;
+		self.pending_len = 0;
+		self.position = 0;
+
+		if pending == 0 {
+			return Ok(());
+		}
+
+		// Pending bytes are never valid on their own, so this always fails.
+		let mut data = Vec::new();
+		decode_to(&self.pending[..pending], &DecodeOptions::new(), &mut data, MODE_COPY)
+			.map_err(|error| offset(error, position))?;
+		Ok(())
+	}
+
+	/// Decodes the input, holding back anything at the end that might continue in the next chunk.
+	fn decode_buffer(&mut self, input: &[u8], output: &mut String) -> Result<()> {
+		output.reserve(input.len());
+
+		// safety: The strict decoder only ever writes whole characters, even on error.
+		let data = unsafe { output.as_mut_vec() };
+		let error = match decode_to(input, &DecodeOptions::new(), data, MODE_COPY) {
+			Ok(_) => {
+				self.position += input.len();
+				return Ok(());
+			}
+			Err(error) => error,
+		};
+
+		// Everything before the error was decoded, only what's left can continue in the next chunk.
+		let tail = &input[error.valid_up_to()..];
+		if !may_continue(&error, tail) {
+			return Err(offset(error, self.position));
+		}
+
+		self.position += input.len() - tail.len();
+		self.pending[..tail.len()].copy_from_slice(tail);
+		self.pending_len = tail.len();
+		Ok(())
+	}
+
+	/// Decodes input that only holds complete sequences.
+	fn decode(&mut self, input: &[u8], output: &mut String) -> Result<()> {
+		output.reserve(input.len());
+
+		// safety: The strict decoder only ever writes whole characters, even on error.
+		let data = unsafe { output.as_mut_vec() };
+		decode_to(input, &DecodeOptions::new(), data, MODE_COPY)
+			.map_err(|error| offset(error, self.position))?;
+
+		self.position += input.len();
+		Ok(())
+	}
+}
+
+/// Moves an error from the start of a chunk to where the chunk is in the whole input.
+#[inline]
+fn offset(error: Error, position: usize) -> Error {
+	Error::new(error.kind().clone(), position + error.valid_up_to(), error.error_len())
+}
+
+/// Returns whether an error at the start of the tail might go away once the next chunk arrives.
+///
+/// That's a sequence that was cut off by the end of the chunk,
+/// or a high surrogate that's only followed by what could be the start of a low one.
+fn may_continue(error: &Error, tail: &[u8]) -> bool {
+	match error.kind() {
+		// The decoder only reports the end of input when the sequence runs into it.
+		ErrorKind::UnexpectedEnd {
+			..
+		} => true,
+		ErrorKind::LoneSurrogate => {
+			let high = matches!(tail, [0xED, 0xA0..=0xAF, ..]);
+			let low = matches!(&tail[3..], [] | [0xED] | [0xED, 0xB0..=0xBF]);
+			high && low
+		}
+		_ => false,
+	}
+}
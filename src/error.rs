@@ -0,0 +1,206 @@
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::str::Utf8Error;
+use std::string::FromUtf8Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error that occurred while converting to or from MUTF-8.
+///
+/// Every error knows where in the input it happened. (See `Error::valid_up_to`)
+/// What actually went wrong is described by `Error::kind`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+	kind: ErrorKind,
+	valid_up_to: usize,
+	error_len: Option<usize>,
+}
+
+impl Error {
+	#[inline]
+	pub(crate) fn new(kind: ErrorKind, valid_up_to: usize, error_len: Option<usize>) -> Self {
+		Error {
+			kind,
+			valid_up_to,
+			error_len,
+		}
+	}
+
+	/// Returns what went wrong.
+	#[inline]
+	pub fn kind(&self) -> &ErrorKind {
+		&self.kind
+	}
+
+	/// Returns the index in the input up to which valid data was verified.
+	///
+	/// This is also the offset of the sequence that caused the error,
+	/// so `&input[..error.valid_up_to()]` can be salvaged, just like with `std::str::Utf8Error`.
+	#[inline]
+	pub fn valid_up_to(&self) -> usize {
+		self.valid_up_to
+	}
+
+	/// Returns the length of the invalid sequence, in bytes.
+	///
+	/// Returns `None` if the input ended before the sequence was complete.
+	/// Just like `std::str::Utf8Error::error_len`, this is the number of bytes to skip to resume after the invalid sequence.
+	#[inline]
+	pub fn error_len(&self) -> Option<usize> {
+		self.error_len
+	}
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+		match &self.kind {
+			ErrorKind::UnexpectedEnd {
+				expected,
+			} => write!(f, "Unexpected end of input. [Unable to decode a {} encoding at byte {}]", expected, self.valid_up_to),
+			ErrorKind::InvalidUtf8(_) | ErrorKind::InvalidSequence => write!(f, "Invalid UTF-8 input. [{} at byte {}]", self.kind, self.valid_up_to),
+			kind => write!(f, "Invalid MUTF-8 input. [{} at byte {}]", kind, self.valid_up_to),
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match &self.kind {
+			ErrorKind::InvalidUtf8(error) => Some(error),
+			_ => None,
+		}
+	}
+}
+
+impl From<Utf8Error> for Error {
+	fn from(err: Utf8Error) -> Self {
+		Error::new(ErrorKind::InvalidUtf8(err), err.valid_up_to(), err.error_len())
+	}
+}
+
+impl From<FromUtf8Error> for Error {
+	fn from(err: FromUtf8Error) -> Self {
+		err.utf8_error().into()
+	}
+}
+
+impl From<Error> for IoError {
+	fn from(err: Error) -> Self {
+		IoError::new(IoErrorKind::InvalidData, err)
+	}
+}
+
+/// The output buffer was too small to hold the converted string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeedMore {
+	needed: usize,
+}
+
+impl NeedMore {
+	#[inline]
+	pub(crate) fn new(needed: usize) -> Self {
+		NeedMore {
+			needed,
+		}
+	}
+
+	/// Returns the length the output buffer needs to be, in bytes.
+	#[inline]
+	pub fn needed(&self) -> usize {
+		self.needed
+	}
+}
+
+impl Display for NeedMore {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+		write!(f, "Output buffer too small. [{} bytes needed]", self.needed)
+	}
+}
+
+impl std::error::Error for NeedMore {
+}
+
+/// What went wrong during a conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+	/// The input ended partway through a sequence.
+	UnexpectedEnd {
+		/// The kind of sequence that was cut off.
+		expected: Expected,
+	},
+	/// A raw nul byte.
+	/// MUTF-8 always encodes nul as the two byte sequence `C0 80`.
+	Nul,
+	/// A continuation byte (`10xxxxxx`) where the start of a new sequence was expected.
+	ContinuationByte,
+	/// A lead byte that wasn't followed by a continuation byte (`10xxxxxx`).
+	ExpectedContinuation,
+	/// A sequence that uses more bytes than needed for the value it encodes.
+	/// The only overlong form MUTF-8 allows is the nul encoding, `C0 80`.
+	Overlong,
+	/// The lead byte of a UTF-8 four byte encoding (`11110xxx`).
+	/// MUTF-8 encodes supplementary characters as a surrogate pair instead, which takes six bytes.
+	FourByte,
+	/// A byte that never appears in MUTF-8. (`0xF8..=0xFF`)
+	InvalidByte,
+	/// A surrogate that isn't part of a pair.
+	/// These are legal MUTF-8, but can't be represented in UTF-8.
+	LoneSurrogate,
+	/// A four byte encoding of a value above U+10FFFF.
+	/// (Only reported when four byte encodings are allowed)
+	OutOfRange,
+	/// The input wasn't valid UTF-8.
+	InvalidUtf8(Utf8Error),
+	/// A sequence that isn't valid UTF-8, in input that's validated as it's written. (See `Mutf8Writer`)
+	InvalidSequence,
+}
+
+impl Display for ErrorKind {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+		match self {
+			Self::UnexpectedEnd {
+				expected,
+			} => write!(f, "Unexpected end of input while decoding a {} encoding", expected),
+			Self::Nul => f.write_str("Found a raw nul byte, which must be encoded as C0 80"),
+			Self::ContinuationByte => f.write_str("Found a continuation byte without a lead byte"),
+			Self::ExpectedContinuation => f.write_str("Expected a continuation byte"),
+			Self::Overlong => f.write_str("Found an overlong encoding"),
+			Self::FourByte => f.write_str("Found a four byte encoding, which must be encoded as a surrogate pair"),
+			Self::InvalidByte => f.write_str("Found a byte that never appears in MUTF-8"),
+			Self::LoneSurrogate => f.write_str("Found a surrogate that isn't part of a pair"),
+			Self::OutOfRange => f.write_str("Found a four byte encoding above U+10FFFF"),
+			// The error's own index is left out, as it's only relative to the slice it was checked on.
+			Self::InvalidUtf8(error) => match error.error_len() {
+				Some(_) => f.write_str("Found an invalid UTF-8 sequence"),
+				None => f.write_str("Unexpected end of input partway through a UTF-8 sequence"),
+			},
+			Self::InvalidSequence => f.write_str("Found an invalid UTF-8 sequence"),
+		}
+	}
+}
+
+/// The kind of sequence the decoder was in the middle of when the input ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Expected {
+	/// A two byte encoding. (`110xxxxx 10xxxxxx`)
+	TwoByte,
+	/// A three byte encoding. (`1110xxxx 10xxxxxx 10xxxxxx`)
+	///
+	/// This includes each half of a surrogate pair, as MUTF-8 encodes those as two three byte encodings.
+	ThreeByte,
+	/// A UTF-8 four byte encoding. (`11110xxx 10xxxxxx 10xxxxxx 10xxxxxx`)
+	/// (Only reported when four byte encodings are allowed)
+	FourByte,
+}
+
+impl Display for Expected {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+		f.write_str(match self {
+			Self::TwoByte => "two byte",
+			Self::ThreeByte => "three byte",
+			Self::FourByte => "four byte",
+		})
+	}
+}
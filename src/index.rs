@@ -0,0 +1,195 @@
+//! Indexing into a `mstr`, either by byte or by a range of bytes.
+
+use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+use crate::str::mstr;
+
+mod private {
+	pub trait Sealed {}
+
+	impl Sealed for usize {}
+	impl Sealed for std::ops::Range<usize> {}
+	impl Sealed for std::ops::RangeFrom<usize> {}
+	impl Sealed for std::ops::RangeFull {}
+	impl Sealed for std::ops::RangeInclusive<usize> {}
+	impl Sealed for std::ops::RangeTo<usize> {}
+	impl Sealed for std::ops::RangeToInclusive<usize> {}
+}
+
+/// A type that can index into a `mstr`. (See `mstr::get`)
+///
+/// A `usize` indexes a single byte, a range of `usize` slices the string.
+/// A slice can only start and end on a char boundary, see `mstr::is_char_boundary`.
+///
+/// This trait is sealed, and can't be implemented outside of this crate.
+pub trait MstrIndex: private::Sealed {
+	/// The type returned by indexing.
+	type Output: ?Sized;
+
+	/// Returns the output, or `None` if it's out of bounds or not on a char boundary.
+	fn get(self, input: &mstr) -> Option<&Self::Output>;
+
+	/// Returns the output, without checking bounds or char boundaries.
+	///
+	/// # Safety
+	///
+	/// The index has to be in bounds, and any range has to start and end on a char boundary.
+	unsafe fn get_unchecked(self, input: &mstr) -> &Self::Output;
+
+	/// Returns the output, panicking if it's out of bounds or not on a char boundary.
+	fn index(self, input: &mstr) -> &Self::Output;
+}
+
+impl MstrIndex for usize {
+	type Output = u8;
+
+	#[inline]
+	fn get(self, input: &mstr) -> Option<&u8> {
+		input.as_bytes().get(self)
+	}
+
+	#[inline]
+	unsafe fn get_unchecked(self, input: &mstr) -> &u8 {
+		input.as_bytes().get_unchecked(self)
+	}
+
+	#[inline]
+	fn index(self, input: &mstr) -> &u8 {
+		&input.as_bytes()[self]
+	}
+}
+
+impl MstrIndex for Range<usize> {
+	type Output = mstr;
+
+	#[inline]
+	fn get(self, input: &mstr) -> Option<&mstr> {
+		if self.start <= self.end && input.is_char_boundary(self.start) && input.is_char_boundary(self.end) {
+			// safety: Both ends were checked above.
+			Some(unsafe { self.get_unchecked(input) })
+		} else {
+			None
+		}
+	}
+
+	#[inline]
+	unsafe fn get_unchecked(self, input: &mstr) -> &mstr {
+		mstr::from_mutf8_unchecked(input.as_bytes().get_unchecked(self))
+	}
+
+	#[inline]
+	fn index(self, input: &mstr) -> &mstr {
+		let len = input.len();
+		if self.start > self.end {
+			panic!("slice index starts at {} but ends at {}", self.start, self.end);
+		}
+		if self.end > len {
+			panic!("byte index {} is out of bounds of a string of length {}", self.end, len);
+		}
+		for index in [self.start, self.end] {
+			if !input.is_char_boundary(index) {
+				panic!("byte index {} is not a char boundary", index);
+			}
+		}
+		// safety: Both ends were checked above.
+		unsafe { self.get_unchecked(input) }
+	}
+}
+
+impl MstrIndex for RangeFrom<usize> {
+	type Output = mstr;
+
+	#[inline]
+	fn get(self, input: &mstr) -> Option<&mstr> {
+		(self.start..input.len()).get(input)
+	}
+
+	#[inline]
+	unsafe fn get_unchecked(self, input: &mstr) -> &mstr {
+		(self.start..input.len()).get_unchecked(input)
+	}
+
+	#[inline]
+	fn index(self, input: &mstr) -> &mstr {
+		(self.start..input.len()).index(input)
+	}
+}
+
+impl MstrIndex for RangeTo<usize> {
+	type Output = mstr;
+
+	#[inline]
+	fn get(self, input: &mstr) -> Option<&mstr> {
+		(0..self.end).get(input)
+	}
+
+	#[inline]
+	unsafe fn get_unchecked(self, input: &mstr) -> &mstr {
+		(0..self.end).get_unchecked(input)
+	}
+
+	#[inline]
+	fn index(self, input: &mstr) -> &mstr {
+		(0..self.end).index(input)
+	}
+}
+
+impl MstrIndex for RangeFull {
+	type Output = mstr;
+
+	#[inline]
+	fn get(self, input: &mstr) -> Option<&mstr> {
+		Some(input)
+	}
+
+	#[inline]
+	unsafe fn get_unchecked(self, input: &mstr) -> &mstr {
+		input
+	}
+
+	#[inline]
+	fn index(self, input: &mstr) -> &mstr {
+		input
+	}
+}
+
+impl MstrIndex for RangeInclusive<usize> {
+	type Output = mstr;
+
+	#[inline]
+	fn get(self, input: &mstr) -> Option<&mstr> {
+		let end = self.end().checked_add(1)?;
+		(*self.start()..end).get(input)
+	}
+
+	#[inline]
+	unsafe fn get_unchecked(self, input: &mstr) -> &mstr {
+		(*self.start()..*self.end() + 1).get_unchecked(input)
+	}
+
+	#[inline]
+	fn index(self, input: &mstr) -> &mstr {
+		let end = self.end().checked_add(1)
+			.unwrap_or_else(|| panic!("attempted to index a string up to the maximum usize"));
+		(*self.start()..end).index(input)
+	}
+}
+
+impl MstrIndex for RangeToInclusive<usize> {
+	type Output = mstr;
+
+	#[inline]
+	fn get(self, input: &mstr) -> Option<&mstr> {
+		(0..=self.end).get(input)
+	}
+
+	#[inline]
+	unsafe fn get_unchecked(self, input: &mstr) -> &mstr {
+		(0..=self.end).get_unchecked(input)
+	}
+
+	#[inline]
+	fn index(self, input: &mstr) -> &mstr {
+		(0..=self.end).index(input)
+	}
+}
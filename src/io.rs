@@ -0,0 +1,310 @@
+//! Adapters that transcode `std::io` streams.
+
+use std::io::{BufRead, Read, Result as IoResult, Write};
+
+use crate::decoder::Decoder;
+use crate::error::{Error, ErrorKind, Expected};
+use crate::mutf8::encode_into;
+
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// A reader that decodes MUTF-8 from an inner reader, and yields it as UTF-8.
+///
+/// Sequences can be split across reads of the inner reader.
+/// Memory use is bounded by the capacity, as the input is decoded a buffer at a time.
+///
+/// Invalid input is reported as an `std::io::Error` of kind `InvalidData`, wrapping the crate's `Error`.
+/// Everything before it is yielded first, and once it's reported, every read after that fails with the same error.
+///
+/// ```
+/// use std::io::Read;
+/// use mutf8::Mutf8Reader;
+///
+/// let mut reader = Mutf8Reader::new(&b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80"[..]);
+/// let mut output = String::new();
+/// reader.read_to_string(&mut output).unwrap();
+/// assert_eq!(output, "a\0\u{1F600}");
+/// ```
+#[derive(Debug)]
+pub struct Mutf8Reader<R> {
+	inner: R,
+	decoder: Decoder,
+	input: Box<[u8]>,
+	output: String,
+	pos: usize,
+	eof: bool,
+	error: Option<Error>,
+}
+
+impl<R: Read> Mutf8Reader<R> {
+	/// Creates a reader, with a default capacity. (Currently 8 KiB)
+	pub fn new(inner: R) -> Self {
+		Self::with_capacity(DEFAULT_CAPACITY, inner)
+	}
+
+	/// Creates a reader, that reads up to `capacity` bytes at a time from the inner reader.
+	///
+	/// # Panics
+	///
+	/// Panics if `capacity` is zero.
+	pub fn with_capacity(capacity: usize, inner: R) -> Self {
+		assert!(capacity > 0, "capacity must be greater than zero");
+		Mutf8Reader {
+			inner,
+			decoder: Decoder::new(),
+			input: vec![0; capacity].into_boxed_slice(),
+			output: String::with_capacity(capacity),
+			pos: 0,
+			eof: false,
+			error: None,
+		}
+	}
+}
+
+impl<R> Mutf8Reader<R> {
+	/// Returns a reference to the inner reader.
+	pub fn get_ref(&self) -> &R {
+		&self.inner
+	}
+
+	/// Returns a mutable reference to the inner reader.
+	///
+	/// Reading from it directly will corrupt the decoded output.
+	pub fn get_mut(&mut self) -> &mut R {
+		&mut self.inner
+	}
+
+	/// Returns the inner reader.
+	///
+	/// Any input that was read, but not yet yielded, is lost.
+	pub fn into_inner(self) -> R {
+		self.inner
+	}
+}
+
+impl<R: Read> Read for Mutf8Reader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+		let data = self.fill_buf()?;
+		let len = data.len().min(buf.len());
+		buf[..len].copy_from_slice(&data[..len]);
+		self.consume(len);
+		Ok(len)
+	}
+}
+
+impl<R: Read> BufRead for Mutf8Reader<R> {
+	fn fill_buf(&mut self) -> IoResult<&[u8]> {
+		// A chunk can decode to nothing, if it's all held back by the decoder.
+		while self.pos == self.output.len() {
+			if let Some(error) = &self.error {
+				return Err(error.clone().into());
+			}
+			if self.eof {
+				break;
+			}
+
+			self.output.clear();
+			self.pos = 0;
+
+			let len = self.inner.read(&mut self.input)?;
+			let result = if len == 0 {
+				self.eof = true;
+				self.decoder.finish()
+			} else {
+				self.decoder.decode_chunk(&self.input[..len], &mut self.output).map(|_| ())
+			};
+			// Whatever was decoded before the error is yielded first.
+			self.error = result.err();
+		}
+		Ok(&self.output.as_bytes()[self.pos..])
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.pos = (self.pos + amt).min(self.output.len());
+	}
+}
+
+/// A writer that encodes UTF-8 into MUTF-8, and writes it to an inner writer.
+///
+/// Sequences can be split across writes, an incomplete one is held back until the rest of it is written.
+/// The output is buffered, and written to the inner writer once the buffer is full, on `flush`, or on `finish`.
+/// Like `std::io::BufWriter`, dropping the writer writes out the buffer, but ignores any errors, so prefer `finish`.
+///
+/// Input is validated as it's written, anything that isn't UTF-8 is reported as an `std::io::Error` of kind `InvalidData`, wrapping the crate's `Error`.
+/// The valid input before it is accepted first, so the error is reported by the write that starts with it.
+/// An incomplete sequence isn't an error until `finish`, as the rest of it might still be written.
+///
+/// ```
+/// use std::io::Write;
+/// use mutf8::Mutf8Writer;
+///
+/// let mut writer = Mutf8Writer::new(Vec::new());
+/// let (name, c) = ("a", '\u{1F600}');
+/// write!(writer, "{}\0{}", name, c).unwrap();
+/// let output = writer.finish().unwrap();
+/// assert_eq!(output, b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80");
+/// ```
+#[derive(Debug)]
+pub struct Mutf8Writer<W: Write> {
+	inner: Option<W>,
+	output: Vec<u8>,
+	capacity: usize,
+	pending: [u8; 4],
+	pending_len: usize,
+	position: usize,
+}
+
+impl<W: Write> Mutf8Writer<W> {
+	/// Creates a writer, with a default capacity. (Currently 8 KiB)
+	pub fn new(inner: W) -> Self {
+		Self::with_capacity(DEFAULT_CAPACITY, inner)
+	}
+
+	/// Creates a writer, that buffers up to `capacity` bytes of output before writing it to the inner writer.
+	pub fn with_capacity(capacity: usize, inner: W) -> Self {
+		Mutf8Writer {
+			inner: Some(inner),
+			output: Vec::with_capacity(capacity),
+			capacity,
+			pending: [0; 4],
+			pending_len: 0,
+			position: 0,
+		}
+	}
+
+	/// Returns a reference to the inner writer.
+	pub fn get_ref(&self) -> &W {
+		self.inner.as_ref().expect("writer is only taken by finish")
+	}
+
+	/// Returns a mutable reference to the inner writer.
+	///
+	/// Writing to it directly will interleave with the buffered output.
+	pub fn get_mut(&mut self) -> &mut W {
+		self.inner.as_mut().expect("writer is only taken by finish")
+	}
+
+	/// Writes out the buffer, flushes the inner writer, and returns it.
+	///
+	/// Fails if the input ended in the middle of a sequence.
+	pub fn finish(mut self) -> IoResult<W> {
+		if self.pending_len > 0 {
+			let expected = match sequence_width(self.pending[0]) {
+				2 => Expected::TwoByte,
+				3 => Expected::ThreeByte,
+				_ => Expected::FourByte,
+			};
+			let kind = ErrorKind::UnexpectedEnd {
+				expected,
+			};
+			return Err(Error::new(kind, self.position, None).into());
+		}
+		self.flush()?;
+		Ok(self.inner.take().expect("writer is only taken by finish"))
+	}
+
+	fn flush_output(&mut self) -> IoResult<()> {
+		if !self.output.is_empty() {
+			let inner = self.inner.as_mut().expect("writer is only taken by finish");
+			inner.write_all(&self.output)?;
+			self.output.clear();
+		}
+		Ok(())
+	}
+
+	/// Completes the pending sequence with the start of the input, returning how much of the input was used.
+	fn complete_pending(&mut self, input: &[u8]) -> IoResult<usize> {
+		let width = sequence_width(self.pending[0]);
+		let used = (width - self.pending_len).min(input.len());
+		self.pending[self.pending_len..self.pending_len + used].copy_from_slice(&input[..used]);
+		self.pending_len += used;
+
+		match std::str::from_utf8(&self.pending[..self.pending_len]) {
+			Ok(data) => {
+				encode_into(data, &mut self.output);
+				self.position += self.pending_len;
+				self.pending_len = 0;
+			}
+			Err(error) if error.error_len().is_none() => {
+				// Still incomplete.
+			}
+			Err(error) => {
+				self.pending_len -= used;
+				return Err(invalid_sequence(error.error_len(), self.position).into());
+			}
+		}
+		Ok(used)
+	}
+}
+
+impl<W: Write> Write for Mutf8Writer<W> {
+	fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+		let mut used = 0;
+		if self.pending_len > 0 {
+			used = self.complete_pending(buf)?;
+			if self.pending_len > 0 {
+				return Ok(used);
+			}
+		}
+
+		let input = &buf[used..];
+		let valid = match std::str::from_utf8(input) {
+			Ok(data) => data,
+			Err(error) => {
+				let valid = error.valid_up_to();
+				if error.error_len().is_some() && used + valid == 0 {
+					return Err(invalid_sequence(error.error_len(), self.position).into());
+				}
+				if error.error_len().is_none() {
+					// Hold back the incomplete sequence at the end.
+					let tail = &input[valid..];
+					self.pending[..tail.len()].copy_from_slice(tail);
+					self.pending_len = tail.len();
+					used += tail.len();
+				}
+				// Anything invalid is left to the next write, which reports it.
+				// safety: Validated up to here.
+				unsafe { std::str::from_utf8_unchecked(&input[..valid]) }
+			}
+		};
+		encode_into(valid, &mut self.output);
+		self.position += valid.len();
+		used += valid.len();
+
+		if self.output.len() >= self.capacity {
+			self.flush_output()?;
+		}
+		Ok(used)
+	}
+
+	fn flush(&mut self) -> IoResult<()> {
+		self.flush_output()?;
+		self.get_mut().flush()
+	}
+}
+
+impl<W: Write> Drop for Mutf8Writer<W> {
+	fn drop(&mut self) {
+		if self.inner.is_some() {
+			let _ = self.flush_output();
+		}
+	}
+}
+
+/// Returns the width of the UTF-8 sequence that starts with the lead byte.
+#[inline]
+fn sequence_width(lead: u8) -> usize {
+	match lead {
+		0xC0..=0xDF => 2,
+		0xE0..=0xEF => 3,
+		_ => 4,
+	}
+}
+
+/// Reports an invalid sequence at the offset into the whole input.
+///
+/// The `Utf8Error` isn't kept, as its offsets are only relative to the part of the input it was found in.
+#[inline]
+fn invalid_sequence(error_len: Option<usize>, position: usize) -> Error {
+	Error::new(ErrorKind::InvalidSequence, position, error_len)
+}
@@ -0,0 +1,274 @@
+//! Iterators over the contents of a `mstr`.
+//!
+//! These all rely on the `mstr` invariant, every sequence is complete and well-formed,
+//! so a Java `char` can be decoded from either end without any further checks.
+
+use std::char::REPLACEMENT_CHARACTER;
+use std::iter::FusedIterator;
+
+use crate::str::mstr;
+
+/// Decodes the Java `char` at the start of the input, returning it and its width in bytes.
+///
+/// The input has to be non-empty, valid MUTF-8.
+#[inline]
+pub(crate) fn next_code(bytes: &[u8]) -> (u16, usize) {
+	let byte1 = bytes[0] as u16;
+	if byte1 < 0x80 {
+		(byte1, 1)
+	} else if byte1 < 0xE0 {
+		// Bits in: 110xxxxx 10xxxxxx
+		(((byte1 & 0x1F) << 6) | (bytes[1] as u16 & 0x3F), 2)
+	} else {
+		// Bits in: 1110xxxx 10xxxxxx 10xxxxxx
+		(((byte1 & 0x0F) << 12) | ((bytes[1] as u16 & 0x3F) << 6) | (bytes[2] as u16 & 0x3F), 3)
+	}
+}
+
+/// Decodes the Java `char` at the end of the input, returning it and its width in bytes.
+///
+/// The input has to be non-empty, valid MUTF-8.
+#[inline]
+fn next_code_back(bytes: &[u8]) -> (u16, usize) {
+	let len = bytes.len();
+	let width = if bytes[len - 1] < 0x80 {
+		1
+	} else if bytes[len - 2] >= 0xC0 {
+		2
+	} else {
+		3
+	};
+	(next_code(&bytes[len - width..]).0, width)
+}
+
+#[inline]
+fn is_high_surrogate(code: u16) -> bool {
+	(0xD800..0xDC00).contains(&code)
+}
+
+#[inline]
+fn is_low_surrogate(code: u16) -> bool {
+	(0xDC00..0xE000).contains(&code)
+}
+
+#[inline]
+fn combine(high: u16, low: u16) -> char {
+	let bits = 0x10000 + (((high as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00);
+	// safety: A surrogate pair always combines into a supplementary character.
+	unsafe { std::char::from_u32_unchecked(bits) }
+}
+
+/// Decodes the character at the start of the input, returning it and its width in bytes.
+///
+/// Lone surrogates are decoded as U+FFFD REPLACEMENT CHARACTER.
+#[inline]
+fn next_char(bytes: &[u8]) -> Option<(char, usize)> {
+	if bytes.is_empty() {
+		return None;
+	}
+
+	let (code, width) = next_code(bytes);
+	if is_high_surrogate(code) && width < bytes.len() {
+		let (low, low_width) = next_code(&bytes[width..]);
+		if is_low_surrogate(low) {
+			return Some((combine(code, low), width + low_width));
+		}
+	}
+	Some((std::char::from_u32(code as u32).unwrap_or(REPLACEMENT_CHARACTER), width))
+}
+
+/// Decodes the character at the end of the input, returning it and its width in bytes.
+///
+/// Lone surrogates are decoded as U+FFFD REPLACEMENT CHARACTER.
+#[inline]
+fn next_char_back(bytes: &[u8]) -> Option<(char, usize)> {
+	if bytes.is_empty() {
+		return None;
+	}
+
+	let (code, width) = next_code_back(bytes);
+	if is_low_surrogate(code) && width < bytes.len() {
+		let (high, high_width) = next_code_back(&bytes[..bytes.len() - width]);
+		if is_high_surrogate(high) {
+			return Some((combine(high, code), width + high_width));
+		}
+	}
+	Some((std::char::from_u32(code as u32).unwrap_or(REPLACEMENT_CHARACTER), width))
+}
+
+/// An iterator over the characters of a `mstr`.
+///
+/// Lone surrogates are yielded as U+FFFD REPLACEMENT CHARACTER.
+///
+/// Created by `mstr::chars`.
+#[derive(Debug, Clone)]
+pub struct Chars<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> Chars<'a> {
+	#[inline]
+	pub(crate) fn new(input: &'a mstr) -> Self {
+		Chars {
+			bytes: input.as_bytes(),
+		}
+	}
+
+	/// Returns what's left of the string.
+	#[inline]
+	pub fn as_mstr(&self) -> &'a mstr {
+		// safety: The iterator only ever steps over whole characters.
+		unsafe {
+			mstr::from_mutf8_unchecked(self.bytes)
+		}
+	}
+}
+
+impl Iterator for Chars<'_> {
+	type Item = char;
+
+	#[inline]
+	fn next(&mut self) -> Option<char> {
+		let (c, width) = next_char(self.bytes)?;
+		self.bytes = &self.bytes[width..];
+		Some(c)
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		// A character takes between one and six bytes.
+		let len = self.bytes.len();
+		(len.div_ceil(6), Some(len))
+	}
+}
+
+impl DoubleEndedIterator for Chars<'_> {
+	#[inline]
+	fn next_back(&mut self) -> Option<char> {
+		let (c, width) = next_char_back(self.bytes)?;
+		self.bytes = &self.bytes[..self.bytes.len() - width];
+		Some(c)
+	}
+}
+
+impl FusedIterator for Chars<'_> {
+}
+
+/// An iterator over the characters of a `mstr`, and their byte offsets.
+///
+/// The offsets are into the MUTF-8 bytes, not the UTF-8 the characters would be encoded as.
+/// Lone surrogates are yielded as U+FFFD REPLACEMENT CHARACTER.
+///
+/// Created by `mstr::char_indices`.
+#[derive(Debug, Clone)]
+pub struct CharIndices<'a> {
+	offset: usize,
+	chars: Chars<'a>,
+}
+
+impl<'a> CharIndices<'a> {
+	#[inline]
+	pub(crate) fn new(input: &'a mstr) -> Self {
+		CharIndices {
+			offset: 0,
+			chars: Chars::new(input),
+		}
+	}
+
+	/// Returns what's left of the string.
+	#[inline]
+	pub fn as_mstr(&self) -> &'a mstr {
+		self.chars.as_mstr()
+	}
+
+	/// Returns the byte offset of the next character, or the length of the string if there's none left.
+	#[inline]
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+}
+
+impl Iterator for CharIndices<'_> {
+	type Item = (usize, char);
+
+	#[inline]
+	fn next(&mut self) -> Option<(usize, char)> {
+		let (c, width) = next_char(self.chars.bytes)?;
+		self.chars.bytes = &self.chars.bytes[width..];
+
+		let offset = self.offset;
+		self.offset += width;
+		Some((offset, c))
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.chars.size_hint()
+	}
+}
+
+impl DoubleEndedIterator for CharIndices<'_> {
+	#[inline]
+	fn next_back(&mut self) -> Option<(usize, char)> {
+		let c = self.chars.next_back()?;
+		Some((self.offset + self.chars.bytes.len(), c))
+	}
+}
+
+impl FusedIterator for CharIndices<'_> {
+}
+
+/// An iterator over the UTF-16 code units of a `mstr`.
+///
+/// These are the Java `char`s the string was encoded from, so lone surrogates are yielded as is.
+///
+/// Created by `mstr::encode_utf16`.
+#[derive(Debug, Clone)]
+pub struct EncodeUtf16<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> EncodeUtf16<'a> {
+	#[inline]
+	pub(crate) fn new(input: &'a mstr) -> Self {
+		EncodeUtf16 {
+			bytes: input.as_bytes(),
+		}
+	}
+}
+
+impl Iterator for EncodeUtf16<'_> {
+	type Item = u16;
+
+	#[inline]
+	fn next(&mut self) -> Option<u16> {
+		if self.bytes.is_empty() {
+			return None;
+		}
+		let (code, width) = next_code(self.bytes);
+		self.bytes = &self.bytes[width..];
+		Some(code)
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		// A code unit takes between one and three bytes.
+		let len = self.bytes.len();
+		(len.div_ceil(3), Some(len))
+	}
+}
+
+impl DoubleEndedIterator for EncodeUtf16<'_> {
+	#[inline]
+	fn next_back(&mut self) -> Option<u16> {
+		if self.bytes.is_empty() {
+			return None;
+		}
+		let (code, width) = next_code_back(self.bytes);
+		self.bytes = &self.bytes[..self.bytes.len() - width];
+		Some(code)
+	}
+}
+
+impl FusedIterator for EncodeUtf16<'_> {
+}
@@ -1,32 +1,88 @@
-use crate::error::{Result, Error, Mode, Expected, Position};
+use crate::ascii::{ascii_run, is_ascii};
+use crate::error::{Result, Error, ErrorKind, Expected, NeedMore};
+use crate::options::{DecodeOptions, EncodeOptions, Surrogates};
 
 use std::borrow::Cow;
 
-pub fn utf8_to_mutf8(input: &[u8]) -> Result<Cow<[u8]>> {
-	let len = input.len();
-	if len == 0 {
-		return Ok(Cow::Borrowed(input));
-	}
+const MODE_BORROW: u8 = 0;
+pub(crate) const MODE_COPY: u8 = 1;
 
-	const MODE_BORROW: u8 = 0;
-	const MODE_COPY: u8 = 1;
+/// Converts UTF-8 bytes into MUTF-8.
+///
+/// The input is validated first, so an error is returned for anything that isn't valid UTF-8.
+/// The error holds the offset of the first invalid byte. (See `Utf8Error::valid_up_to`)
+///
+/// If you already have a `&str`, use `encode`, which can't fail.
+pub fn utf8_to_mutf8(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+	let input = std::str::from_utf8(input)?;
+	Ok(encode(input))
+}
 
-	let mut mode = MODE_BORROW;
+/// Converts a string into MUTF-8.
+///
+/// The input is borrowed if it doesn't contain any nul bytes or supplementary characters.
+///
+/// See `EncodeOptions` to produce other variants, such as CESU-8.
+pub fn encode(input: &str) -> Cow<'_, [u8]> {
+	encode_with(input, &EncodeOptions::new())
+}
+
+pub(crate) fn encode_with<'a>(input: &'a str, options: &EncodeOptions) -> Cow<'a, [u8]> {
+	// safety: A valid UTF-8 string is also valid WTF-8.
+	unsafe { encode_wtf8(input.as_bytes(), options) }
+}
+
+/// # Safety
+///
+/// The input has to be valid WTF-8. (UTF-8 that may contain surrogates)
+/// Every sequence is assumed to be complete.
+pub(crate) unsafe fn encode_wtf8<'a>(input: &'a [u8], options: &EncodeOptions) -> Cow<'a, [u8]> {
+	if input.is_empty() {
+		return Cow::Borrowed(input);
+	}
 
 	let mut data = vec![];
+	if encode_wtf8_to(input, options, &mut data, MODE_BORROW) == MODE_BORROW {
+		Cow::Borrowed(input)
+	} else {
+		Cow::Owned(data)
+	}
+}
+
+/// Encodes the input, appending it to `data`.
+///
+/// In `MODE_BORROW`, `data` has to be empty, and nothing is written until the first sequence that has to be encoded differently.
+/// Returns the mode it ended in, if that's still `MODE_BORROW`, the input can be used as is.
+///
+/// # Safety
+///
+/// The input has to be valid WTF-8. (See `encode_wtf8`)
+unsafe fn encode_wtf8_to(input: &[u8], options: &EncodeOptions, data: &mut Vec<u8>, mut mode: u8) -> u8 {
+	let len = input.len();
 	let mut i = 0;
 	while i < len {
 		let mark = i;
 
+		if is_ascii(input[i]) {
+			// Runs of ascii are the same in both encodings, so they can be skipped over in bulk.
+			i += ascii_run(&input[i..]);
+			if mode == MODE_COPY {
+				data.extend(&input[mark..i]);
+			}
+			continue;
+		}
+
+		// safety: The input is valid WTF-8, so every sequence is complete.
 		let byte1 = unsafe { *input.get_unchecked(i) };
 		i += 1;
 
 		// nul bytes and bytes starting with 11110xxx are somewhat special
 		if byte1 & 0x80 == 0 {
 			// 1-byte encoding
-			if byte1 == 0 {
+			if byte1 == 0 && options.escape_nul {
 				if mode == MODE_BORROW {
 					mode = MODE_COPY;
+					data.reserve_exact(mark + encoded_len(&input[mark..], options));
 					let run = &input[0..mark];
 					data.extend(run);
 				}
@@ -38,57 +94,265 @@ pub fn utf8_to_mutf8(input: &[u8]) -> Result<Cow<[u8]>> {
 		} else if byte1 & 0xE0 == 0xC0 {
 			// 2-byte encoding
 			if mode == MODE_COPY {
-				data.push(byte1);
-				let byte2 = *input.get(i).ok_or(Error::EndOfInput(Mode::Encoding, Expected::TwoByte, Position::Two))?;
-				i += 1;
-				data.push(byte2);
+				data.extend(&input[mark..mark + 2]);
 			}
+			i += 1;
 		} else if byte1 & 0xF0 == 0xE0 {
-			// 3-byte encoding
+			// 3-byte encoding (Lone surrogates included, they're the same in MUTF-8)
 			if mode == MODE_COPY {
-				data.push(byte1);
-				let byte2 = *input.get(i).ok_or(Error::EndOfInput(Mode::Encoding, Expected::ThreeByte, Position::Two))?;
-				i += 1;
-				data.push(byte2);
-				let byte3 = *input.get(i).ok_or(Error::EndOfInput(Mode::Encoding, Expected::ThreeByte, Position::Three))?;
-				i += 1;
-				data.push(byte3);
-			}
-		} else if byte1 & 0xF8 == 0xF0 {
+				data.extend(&input[mark..mark + 3]);
+			}
+			i += 2;
+		} else if !options.surrogate_pairs {
+			// 4-byte encoding, kept as is
+			if mode == MODE_COPY {
+				data.extend(&input[mark..mark + 4]);
+			}
+			i += 3;
+		} else {
 			if mode == MODE_BORROW {
 				mode = MODE_COPY;
+				data.reserve_exact(mark + encoded_len(&input[mark..], options));
 				let run = &input[0..mark];
 				data.extend(run);
 			}
 
-			// Beginning of 4-byte encoding, turn into 2 3-byte encodings
-			// Bits in: 11110xxx 10xxxxxx 10xxxxxx 10xxxxxx
-			let byte2 = *input.get(i).ok_or(Error::EndOfInput(Mode::Encoding, Expected::FourByte, Position::Two))?;
-			i += 1;
-			let byte3 = *input.get(i).ok_or(Error::EndOfInput(Mode::Encoding, Expected::FourByte, Position::Three))?;
-			i += 1;
-			let byte4 = *input.get(i).ok_or(Error::EndOfInput(Mode::Encoding, Expected::FourByte, Position::Four))?;
+			// safety: The input is valid WTF-8, so every sequence is complete.
+			let pair = surrogate_pair(unsafe { input.get_unchecked(mark..mark + 4) });
+			data.extend(&pair);
+			i += 3;
+		}
+	}
+	mode
+}
+
+/// Turns a UTF-8 4-byte encoding into a MUTF-8 surrogate pair, 2 3-byte encodings.
+#[inline]
+fn surrogate_pair(bytes: &[u8]) -> [u8; 6] {
+	// Bits in: 11110xxx 10xxxxxx 10xxxxxx 10xxxxxx
+	let (byte1, byte2, byte3, byte4) = (bytes[0], bytes[1], bytes[2], bytes[3]);
+
+	// Reconstruct full 21-bit value
+	let mut bits: u32 = ((byte1 as u32) & 0x07) << 18;
+	bits += ((byte2 as u32) & 0x3F) << 12;
+	bits += ((byte3 as u32) & 0x3F) << 6;
+	bits += (byte4 as u32) & 0x3F;
+
+	[
+		// Bits out: 11101101 1010xxxx 10xxxxxx
+		0xED,
+		(0xA0 + (((bits >> 16) - 1) & 0x0F)) as u8,
+		(0x80 + ((bits >> 10) & 0x3F)) as u8,
+		// Bits out: 11101101 1011xxxx 10xxxxxx
+		0xED,
+		(0xB0 + ((bits >> 6) & 0x0F)) as u8,
+		byte4,
+	]
+}
+
+/// Converts a string into MUTF-8, appending it to `output`.
+///
+/// This doesn't allocate anything besides growing `output`.
+pub fn encode_into(input: &str, output: &mut Vec<u8>) {
+	output.reserve(input.len());
+
+	// safety: A valid UTF-8 string is also valid WTF-8.
+	unsafe {
+		encode_wtf8_to(input.as_bytes(), &EncodeOptions::new(), output, MODE_COPY);
+	}
+}
+
+/// Converts a string into MUTF-8, writing it to the start of `output`.
+///
+/// Returns the number of bytes written.
+/// If `output` is too small, nothing is written, and the error holds the length that's needed. (See `mutf8_len`)
+pub fn encode_to_slice(input: &str, output: &mut [u8]) -> std::result::Result<usize, NeedMore> {
+	let needed = mutf8_len(input);
+	if needed > output.len() {
+		return Err(NeedMore::new(needed));
+	}
+
+	let input = input.as_bytes();
+	let mut i = 0;
+	let mut j = 0;
+	while i < input.len() {
+		let byte1 = input[i];
+		let width = if is_ascii(byte1) {
+			ascii_run(&input[i..])
+		} else if byte1 == 0 {
+			output[j..j + 2].copy_from_slice(&[0xC0, 0x80]);
 			i += 1;
+			j += 2;
+			continue;
+		} else if byte1 < 0xE0 {
+			2
+		} else if byte1 < 0xF0 {
+			3
+		} else {
+			output[j..j + 6].copy_from_slice(&surrogate_pair(&input[i..i + 4]));
+			i += 4;
+			j += 6;
+			continue;
+		};
+		output[j..j + width].copy_from_slice(&input[i..i + width]);
+		i += width;
+		j += width;
+	}
+	Ok(j)
+}
+
+/// Returns the length of the string once it's encoded as MUTF-8, in bytes.
+///
+/// This is the length `encode` produces, and what the length prefix of JNI's `GetStringUTFLength` or Java's `DataOutput.writeUTF` holds.
+pub fn mutf8_len(input: &str) -> usize {
+	encoded_len(input.as_bytes(), &EncodeOptions::new())
+}
+
+/// Returns the exact length of the WTF-8 input once it's encoded following the options.
+pub(crate) fn encoded_len(input: &[u8], options: &EncodeOptions) -> usize {
+	let mut len = input.len();
+	let mut i = 0;
+	while i < input.len() {
+		let byte = input[i];
+		if is_ascii(byte) {
+			i += ascii_run(&input[i..]);
+			continue;
+		}
+		if byte == 0 && options.escape_nul {
+			// 1 byte turns into `C0 80`
+			len += 1;
+		} else if byte >= 0xF0 && options.surrogate_pairs {
+			// 4 bytes turn into a 6 byte surrogate pair
+			len += 2;
+		}
+		i += 1;
+	}
+	len
+}
+
+/// Returns the exact length of the input from `start` onwards once it's decoded following the options.
+///
+/// The input is validated along the way, errors are reported with offsets into the whole input.
+pub(crate) fn decoded_len(input: &[u8], start: usize, options: &DecodeOptions) -> Result<usize> {
+	let mut len = 0;
+	let mut i = start;
+	while i < input.len() {
+		let mark = i;
+		if is_ascii(input[i]) {
+			i += ascii_run(&input[i..]);
+			len += i - mark;
+			continue;
+		}
+		match next_unit(input, i, options)? {
+			Unit::Same(width) => {
+				i += width;
+				len += width;
+			}
+			Unit::Char(c, width) => {
+				i += width;
+				len += c.len_utf8();
+			}
+			Unit::Surrogate => {
+				if options.surrogates == Surrogates::Error {
+					return Err(invalid(ErrorKind::LoneSurrogate, mark, 3));
+				}
+				// Either kept as is, or replaced with U+FFFD, both of which are three bytes.
+				i += 3;
+				len += 3;
+			}
+		}
+	}
+	Ok(len)
+}
 
-			// Reconstruct full 21-bit value
-			let mut bits: u32 = ((byte1 as u32) & 0x07) << 18;
-			bits += ((byte2 as u32) & 0x3F) << 12;
-			bits += ((byte3 as u32) & 0x3F) << 6;
-			bits += (byte4 as u32) & 0x3F;
+/// Converts MUTF-8 into UTF-8.
+///
+/// The input is strictly validated, any sequence that isn't legal MUTF-8 is reported as an error.
+/// Lone surrogates are legal MUTF-8, but can't be represented in UTF-8, so they're reported as an error too.
+/// Use `mutf8_to_wtf8` to keep them.
+///
+/// The input is borrowed if it doesn't contain any encoded nul bytes or surrogate pairs.
+///
+/// See `DecodeOptions` for a decoder that can be configured to accept other variants.
+pub fn mutf8_to_utf8(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+	decode_with(input, &DecodeOptions::new())
+}
 
-			// Bits out: 11101101 1010xxxx 10xxxxxx
-			data.push(0xED);
-			data.push((0xA0 + (((bits >> 16) - 1) & 0x0F)) as u8);
-			data.push((0x80 + ((bits >> 10) & 0x3F)) as u8);
+/// Converts MUTF-8 into UTF-8 in place, following the same rules as `mutf8_to_utf8`.
+///
+/// Decoding never makes the data longer, so the UTF-8 ends up at the start of `data`, and its length is returned.
+/// The rest of `data` is left with unspecified contents, so a `Vec` should be truncated afterwards:
+///
+/// ```
+/// let mut data = b"a\xC0\x80b".to_vec();
+/// let len = mutf8::mutf8_to_utf8_in_place(&mut data).unwrap();
+/// data.truncate(len);
+/// assert_eq!(data, b"a\0b");
+/// ```
+///
+/// The input is validated before anything is changed, so on error `data` is left as it was.
+pub fn mutf8_to_utf8_in_place(data: &mut [u8]) -> Result<usize> {
+	let options = DecodeOptions::new();
+	let len = decoded_len(data, 0, &options)?;
+	if len == data.len() {
+		// Every sequence that's decoded differently is shorter, so there's nothing to do.
+		return Ok(len);
+	}
 
-			// Bits out: 11101101 1011xxxx 10xxxxxx
-			data.push(0xED);
-			data.push((0xB0 + ((bits >> 6) & 0x0F)) as u8);
-			data.push(byte4);
+	// The write position never overtakes the read position, so everything left to read is untouched.
+	let mut i = 0;
+	let mut j = 0;
+	while i < data.len() {
+		let mark = i;
+		if is_ascii(data[i]) {
+			i += ascii_run(&data[i..]);
+		} else {
+			match next_unit(data, i, &options)? {
+				Unit::Same(width) => i += width,
+				Unit::Char(c, width) => {
+					i += width;
+					j += c.encode_utf8(&mut data[j..]).len();
+					continue;
+				}
+				Unit::Surrogate => unreachable!("lone surrogates are rejected by the strict decoder"),
+			}
 		}
+		data.copy_within(mark..i, j);
+		j += i - mark;
+	}
+
+	Ok(j)
+}
+
+/// Converts MUTF-8 into a string.
+///
+/// This validates and converts in a single pass, following the same rules as `mutf8_to_utf8`.
+///
+/// The input is borrowed if it doesn't contain any encoded nul bytes or surrogate pairs.
+pub fn decode(input: &[u8]) -> Result<Cow<'_, str>> {
+	let data = decode_with(input, &DecodeOptions::new())?;
+
+	// safety: The strict decoder rejects lone surrogates, which is the only thing that isn't valid UTF-8 once decoded.
+	unsafe {
+		Ok(match data {
+			Cow::Borrowed(data) => Cow::Borrowed(std::str::from_utf8_unchecked(data)),
+			Cow::Owned(data) => Cow::Owned(String::from_utf8_unchecked(data)),
+		})
+	}
+}
+
+/// Checks that the input follows the given options, without converting it.
+pub(crate) fn validate_with(input: &[u8], options: &DecodeOptions) -> Result<()> {
+	decoded_len(input, 0, options).map(|_| ())
+}
+
+pub(crate) fn decode_with<'a>(input: &'a [u8], options: &DecodeOptions) -> Result<Cow<'a, [u8]>> {
+	if input.is_empty() {
+		return Ok(Cow::Borrowed(input));
 	}
 
-	let cow = if mode == MODE_BORROW {
+	let mut data = vec![];
+	let cow = if decode_to(input, options, &mut data, MODE_BORROW)? == MODE_BORROW {
 		Cow::Borrowed(input)
 	} else {
 		Cow::Owned(data)
@@ -97,14 +361,81 @@ pub fn utf8_to_mutf8(input: &[u8]) -> Result<Cow<[u8]>> {
 	Ok(cow)
 }
 
-pub fn mutf8_to_utf8(input: &[u8]) -> Result<Cow<[u8]>> {
+/// Decodes the input, appending it to `data`.
+///
+/// In `MODE_BORROW`, `data` has to be empty, and nothing is written until the first sequence that has to be decoded differently.
+/// Returns the mode it ended in, if that's still `MODE_BORROW`, the input can be used as is.
+/// On error, some of the input may have been written already.
+pub(crate) fn decode_to(input: &[u8], options: &DecodeOptions, data: &mut Vec<u8>, mut mode: u8) -> Result<u8> {
 	let len = input.len();
-	if len == 0 {
-		return Ok(Cow::Borrowed(input));
+	let mut i = 0;
+	while i < len {
+		let mark = i;
+
+		if is_ascii(input[i]) {
+			// Runs of ascii are the same in both encodings, so they can be skipped over in bulk.
+			i += ascii_run(&input[i..]);
+			if mode == MODE_COPY {
+				data.extend(&input[mark..i]);
+			}
+			continue;
+		}
+
+		match next_unit(input, i, options)? {
+			Unit::Same(width) => {
+				i += width;
+				if mode == MODE_COPY {
+					data.extend(&input[mark..i]);
+				}
+			}
+			Unit::Char(c, width) => {
+				i += width;
+				if mode == MODE_BORROW {
+					mode = MODE_COPY;
+					// Decoding never makes the data longer, so this is the only allocation, without another pass over the input.
+					data.reserve_exact(input.len());
+					let run = &input[0..mark];
+					data.extend(run);
+				}
+				push_char(data, c);
+			}
+			Unit::Surrogate => {
+				i += 3;
+				match options.surrogates {
+					Surrogates::Error => return Err(invalid(ErrorKind::LoneSurrogate, mark, 3)),
+					Surrogates::Replace => {
+						if mode == MODE_BORROW {
+							mode = MODE_COPY;
+							data.reserve_exact(input.len());
+							let run = &input[0..mark];
+							data.extend(run);
+						}
+						push_char(data, char::REPLACEMENT_CHARACTER);
+					}
+					Surrogates::Wtf8 => {
+						if mode == MODE_COPY {
+							data.extend(&input[mark..i]);
+						}
+					}
+				}
+			}
+		}
 	}
 
-	const MODE_BORROW: u8 = 0;
-	const MODE_COPY: u8 = 1;
+	Ok(mode)
+}
+
+/// Converts MUTF-8 into UTF-8, replacing anything invalid with U+FFFD REPLACEMENT CHARACTER.
+///
+/// Encoded nul bytes and surrogate pairs are decoded as usual.
+/// Only sequences that are actually broken, along with lone surrogates, are replaced.
+/// Like `String::from_utf8_lossy`, each maximal invalid prefix of a sequence is replaced with a single U+FFFD.
+///
+/// The input is borrowed if it's already valid UTF-8, that doesn't need to be re-encoded.
+pub fn mutf8_to_utf8_lossy(input: &[u8]) -> Cow<'_, str> {
+	let options = DecodeOptions::new().surrogates(Surrogates::Replace);
+
+	let len = input.len();
 
 	let mut mode = MODE_BORROW;
 
@@ -113,97 +444,177 @@ pub fn mutf8_to_utf8(input: &[u8]) -> Result<Cow<[u8]>> {
 	while i < len {
 		let mark = i;
 
-		let byte1 = unsafe { *input.get_unchecked(i) };
-		i += 1;
+		if is_ascii(input[i]) {
+			// Runs of ascii are the same in both encodings, so they can be skipped over in bulk.
+			i += ascii_run(&input[i..]);
+			if mode == MODE_COPY {
+				data.extend(&input[mark..i]);
+			}
+			continue;
+		}
 
-		if byte1 & 0x80 == 0 {
-			// 1 byte encoding
-			if mode == MODE_BORROW {
-				// Nothing to do here as it's valid ascii/utf-8.
+		let (c, width) = match next_unit(input, i, &options) {
+			Ok(Unit::Same(width)) => {
+				i += width;
+				if mode == MODE_COPY {
+					data.extend(&input[mark..i]);
+				}
 				continue;
 			}
-			data.push(byte1);
-		} else if byte1 & 0xE0 == 0xC0 {
-			// 2 byte encoding
-			// Mask out the three bits so we can check if it's equal to the marker bits that say this is a 2 byte encoding.
-			// 0b11100000 = 0xE0
-			// 0b11000000 = 0xC0
-			let byte2 = *input.get(i).ok_or(Error::EndOfInput(Mode::Decoding, Expected::TwoByte, Position::Two))?;
-			i += 1;
-			//				println!("Bytes: {:x} {:x}", byte1, byte2);
+			Ok(Unit::Char(c, width)) => (c, width),
+			Ok(Unit::Surrogate) => (char::REPLACEMENT_CHARACTER, 3),
+			Err(error) => (char::REPLACEMENT_CHARACTER, error.error_len().unwrap_or(len - mark)),
+		};
+		i += width;
 
-			if byte1 != 0xC0 || byte2 != 0x80 {
-				if mode == MODE_BORROW {
-					// Nothing to do here as it's valid ascii/utf-8.
-					continue;
-				}
-				data.push(byte1);
-				data.push(byte2);
+		if mode == MODE_BORROW {
+			mode = MODE_COPY;
+			let run = &input[0..mark];
+			data.extend(run);
+		}
+		push_char(&mut data, c);
+	}
+
+	// safety: Everything was either validated, or replaced with a valid character.
+	unsafe {
+		if mode == MODE_BORROW {
+			Cow::Borrowed(std::str::from_utf8_unchecked(input))
+		} else {
+			Cow::Owned(String::from_utf8_unchecked(data))
+		}
+	}
+}
+
+/// A single decoded sequence.
+pub(crate) enum Unit {
+	/// A sequence of the given width that's encoded exactly the same way in UTF-8.
+	Same(usize),
+	/// A character that has to be re-encoded for UTF-8, along with the width of its sequence.
+	/// (Encoded nul, surrogate pairs, and any overlong encodings that were allowed)
+	Char(char, usize),
+	/// A surrogate that isn't part of a pair, which is always three bytes wide.
+	Surrogate,
+}
+
+/// Reads the sequence that starts at `i`, which has to be in bounds.
+#[inline]
+pub(crate) fn next_unit(input: &[u8], i: usize, options: &DecodeOptions) -> Result<Unit> {
+	let byte1 = input[i];
+	match byte1 {
+		0x00 => {
+			if options.allow_nul {
+				Ok(Unit::Same(1))
 			} else {
-				if mode == MODE_BORROW {
-					mode = MODE_COPY;
-					let run = &input[0..mark];
-					data.extend(run);
+				// MUTF-8 never contains a raw nul byte, it's always encoded as `C0 80`.
+				Err(invalid(ErrorKind::Nul, i, 1))
+			}
+		}
+		0x01..=0x7F => Ok(Unit::Same(1)),
+		0x80..=0xBF => {
+			// A continuation byte can only follow a lead byte.
+			Err(invalid(ErrorKind::ContinuationByte, i, 1))
+		}
+		0xC0..=0xDF => {
+			// 2 byte encoding
+			let byte2 = continuation(input, i, 1, Expected::TwoByte)?;
+			if byte1 == 0xC0 && byte2 == 0x80 && options.allow_escaped_nul {
+				Ok(Unit::Char('\0', 2))
+			} else if byte1 < 0xC2 {
+				// Anything that fits in 7 bits has to use the 1 byte encoding. (Except for nul, handled above)
+				if !options.allow_overlong {
+					return Err(invalid(ErrorKind::Overlong, i, 1));
 				}
-				data.push(0);
+				let bits = ((byte1 as u32 & 0x1F) << 6) | (byte2 as u32 & 0x3F);
+				Ok(Unit::Char(bits as u8 as char, 2))
+			} else {
+				Ok(Unit::Same(2))
 			}
-		} else if byte1 & 0xF0 == 0xE0 {
+		}
+		0xE0..=0xEF => {
 			// 3 byte encoding
-			let byte2 = *input.get(i).ok_or(Error::EndOfInput(Mode::Decoding, Expected::ThreeByte, Position::Two))?;
-			i += 1;
-			let byte3 = *input.get(i).ok_or(Error::EndOfInput(Mode::Decoding, Expected::ThreeByte, Position::Three))?;
-			i += 1;
-			//				println!("{:x} {:x} {:x}", byte1, byte2, byte3);
-			if i + 2 < len && byte1 == 0xED && byte2 & 0xF0 == 0xA0 {
-				// Check if pair encoding...
-
-				let byte4 = *input.get(i).ok_or(Error::EndOfInput(Mode::Decoding, Expected::SixByte, Position::Four))?;
-				let byte5 = *input.get(i + 1).ok_or(Error::EndOfInput(Mode::Decoding, Expected::SixByte, Position::Five))?;
-				let byte6 = *input.get(i + 2).ok_or(Error::EndOfInput(Mode::Decoding, Expected::SixByte, Position::Six))?;
-
-				//					println!("{:x} {:x} {:x}", byte4, byte5, byte6);
-				if byte4 == 0xED && byte5 & 0xF0 == 0xB0 {
-					// Bits in: 11101101 1010xxxx 10xxxxxx
-					// Bits in: 11101101 1011xxxx 10xxxxxx
-
-					i += 2;
+			let byte2 = continuation(input, i, 1, Expected::ThreeByte)?;
+			let overlong = byte1 == 0xE0 && byte2 < 0xA0;
+			if overlong && !options.allow_overlong {
+				// Anything that fits in 11 bits has to use the 2 byte encoding.
+				return Err(invalid(ErrorKind::Overlong, i, 1));
+			}
+			let byte3 = continuation(input, i, 2, Expected::ThreeByte)?;
 
-					let mut bits: u32 = (((byte2 as u32) & 0x0F) + 1) << 16;
-					bits += ((byte3 as u32) & 0x3F) << 10;
-					bits += ((byte5 as u32) & 0x0F) << 6;
-					bits += (byte6 as u32) & 0x3F;
+			let bits = ((byte1 as u32 & 0x0F) << 12) | ((byte2 as u32 & 0x3F) << 6) | (byte3 as u32 & 0x3F);
+			if overlong {
+				// safety: An overlong three byte encoding holds at most 11 bits, which can't be a surrogate.
+				return Ok(Unit::Char(unsafe { std::char::from_u32_unchecked(bits) }, 3));
+			}
+			if byte1 != 0xED || byte2 < 0xA0 {
+				return Ok(Unit::Same(3));
+			}
 
-					// Bits out: 11110xxx 10xxxxxx 10xxxxxx 10xxxxxx
+			if byte2 < 0xB0 {
+				// Check if pair encoding...
+				if let Some(&[0xED, byte5, byte6]) = input.get(i + 3..i + 6) {
+					if byte5 & 0xF0 == 0xB0 && is_continuation(byte6) {
+						// Bits in: 11101101 1010xxxx 10xxxxxx
+						// Bits in: 11101101 1011xxxx 10xxxxxx
+						let mut bits: u32 = (((byte2 as u32) & 0x0F) + 1) << 16;
+						bits += ((byte3 as u32) & 0x3F) << 10;
+						bits += ((byte5 as u32) & 0x0F) << 6;
+						bits += (byte6 as u32) & 0x3F;
 
-					if mode == MODE_BORROW {
-						mode = MODE_COPY;
-						let run = &input[0..mark];
-						data.extend(run);
+						// safety: A surrogate pair always combines into a supplementary character.
+						return Ok(Unit::Char(unsafe { std::char::from_u32_unchecked(bits) }, 6));
 					}
-					// Convert the bits into 4 UTF-8 bytes.
-					data.push((0xF0 + ((bits >> 18) & 0x07)) as u8);
-					data.push((0x80 + ((bits >> 12) & 0x3F)) as u8);
-					data.push((0x80 + ((bits >> 6) & 0x3F)) as u8);
-					data.push((0x80 + (bits & 0x3F)) as u8);
-
-					continue;
 				}
 			}
-			if mode == MODE_BORROW {
-				// Nothing to do here as it's valid ascii/utf-8.
-				continue;
+
+			Ok(Unit::Surrogate)
+		}
+		0xF0..=0xF7 => {
+			if !options.allow_four_byte {
+				// Supplementary characters are encoded as a surrogate pair (six bytes) in MUTF-8.
+				return Err(invalid(ErrorKind::FourByte, i, 1));
 			}
-			data.push(byte1);
-			data.push(byte2);
-			data.push(byte3);
+			let byte2 = continuation(input, i, 1, Expected::FourByte)?;
+			if byte1 == 0xF0 && byte2 < 0x90 {
+				// Anything that fits in 16 bits has to use the 3 byte encoding.
+				return Err(invalid(ErrorKind::Overlong, i, 1));
+			}
+			if byte1 > 0xF4 || (byte1 == 0xF4 && byte2 >= 0x90) {
+				return Err(invalid(ErrorKind::OutOfRange, i, 1));
+			}
+			continuation(input, i, 2, Expected::FourByte)?;
+			continuation(input, i, 3, Expected::FourByte)?;
+			Ok(Unit::Same(4))
 		}
+		_ => Err(invalid(ErrorKind::InvalidByte, i, 1)),
 	}
+}
 
-	let cow = if mode == MODE_BORROW {
-		Cow::Borrowed(input)
-	} else {
-		Cow::Owned(data)
-	};
+/// Reads the continuation byte at `offset` into the sequence that starts at `start`.
+#[inline]
+fn continuation(input: &[u8], start: usize, offset: usize, expected: Expected) -> Result<u8> {
+	match input.get(start + offset) {
+		Some(&byte) if is_continuation(byte) => Ok(byte),
+		Some(_) => Err(invalid(ErrorKind::ExpectedContinuation, start, offset)),
+		None => Err(end_of_input(expected, start)),
+	}
+}
 
-	Ok(cow)
+#[inline]
+fn push_char(data: &mut Vec<u8>, c: char) {
+	data.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+#[inline]
+pub(crate) fn is_continuation(byte: u8) -> bool {
+	byte & 0xC0 == 0x80
+}
+
+#[inline]
+fn end_of_input(expected: Expected, valid_up_to: usize) -> Error {
+	Error::new(ErrorKind::UnexpectedEnd { expected }, valid_up_to, None)
+}
+
+#[inline]
+fn invalid(kind: ErrorKind, valid_up_to: usize, error_len: usize) -> Error {
+	Error::new(kind, valid_up_to, Some(error_len))
 }
@@ -0,0 +1,184 @@
+use std::borrow::Cow;
+
+use crate::error::Result;
+use crate::mutf8::{decode_with, encode_with};
+
+/// How a decoder treats a surrogate that isn't part of a pair.
+///
+/// Lone surrogates are legal MUTF-8, as Java strings can contain them, but UTF-8 can't represent them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Surrogates {
+	/// Reject them with `ErrorKind::LoneSurrogate`.
+	Error,
+	/// Replace each of them with U+FFFD REPLACEMENT CHARACTER.
+	Replace,
+	/// Pass them through as is.
+	///
+	/// The output is then WTF-8 rather than UTF-8, and can't be turned into a `str` if it contains any.
+	Wtf8,
+}
+
+/// A configurable MUTF-8 decoder.
+///
+/// Different producers emit subtly different "modified UTF-8", so each of the strict rules can be relaxed individually.
+/// `DecodeOptions::new()` starts out with the same rules as `mutf8_to_utf8`.
+///
+/// ```
+/// use mutf8::{DecodeOptions, Surrogates};
+///
+/// let options = DecodeOptions::new()
+///     .allow_four_byte(true)
+///     .surrogates(Surrogates::Replace);
+///
+/// let data = options.decode(b"\xF0\x9F\x98\x80 \xED\xA0\xBD").unwrap();
+/// assert_eq!(&*data, "\u{1F600} \u{FFFD}".as_bytes());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeOptions {
+	pub(crate) allow_nul: bool,
+	pub(crate) allow_escaped_nul: bool,
+	pub(crate) allow_four_byte: bool,
+	pub(crate) allow_overlong: bool,
+	pub(crate) surrogates: Surrogates,
+}
+
+impl DecodeOptions {
+	/// Creates a strict decoder, that follows the same rules as `mutf8_to_utf8`.
+	pub const fn new() -> Self {
+		DecodeOptions {
+			allow_nul: false,
+			allow_escaped_nul: true,
+			allow_four_byte: false,
+			allow_overlong: false,
+			surrogates: Surrogates::Error,
+		}
+	}
+
+	/// Creates a strict CESU-8 decoder.
+	///
+	/// CESU-8 is MUTF-8 without the special encoding for nul, so a raw nul byte is accepted and `C0 80` is rejected as overlong.
+	/// Surrogates have to be paired.
+	pub const fn cesu8() -> Self {
+		Self::new()
+			.allow_nul(true)
+			.allow_escaped_nul(false)
+			.surrogates(Surrogates::Error)
+	}
+
+	/// Sets whether a raw nul byte is accepted.
+	pub const fn allow_nul(mut self, allow: bool) -> Self {
+		self.allow_nul = allow;
+		self
+	}
+
+	/// Sets whether nul encoded as `C0 80` is accepted.
+	///
+	/// If not, it's treated like any other overlong encoding. (See `allow_overlong`)
+	pub const fn allow_escaped_nul(mut self, allow: bool) -> Self {
+		self.allow_escaped_nul = allow;
+		self
+	}
+
+	/// Sets whether UTF-8 four byte encodings are accepted, alongside the usual surrogate pairs.
+	///
+	/// Android's JNI `NewStringUTF`, for example, tolerates these.
+	pub const fn allow_four_byte(mut self, allow: bool) -> Self {
+		self.allow_four_byte = allow;
+		self
+	}
+
+	/// Sets whether overlong two and three byte encodings are accepted.
+	///
+	/// Accepted overlong encodings are converted to their shortest form.
+	/// `C0 80` is controlled separately by `allow_escaped_nul`, as that's how MUTF-8 encodes nul.
+	pub const fn allow_overlong(mut self, allow: bool) -> Self {
+		self.allow_overlong = allow;
+		self
+	}
+
+	/// Sets how a surrogate that isn't part of a pair is treated.
+	pub const fn surrogates(mut self, surrogates: Surrogates) -> Self {
+		self.surrogates = surrogates;
+		self
+	}
+
+	/// Converts the input into UTF-8, following these options.
+	///
+	/// The input is borrowed if it's already encoded the same way as UTF-8.
+	pub fn decode<'a>(&self, input: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+		decode_with(input, self)
+	}
+}
+
+impl Default for DecodeOptions {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A configurable MUTF-8 encoder.
+///
+/// MUTF-8 differs from UTF-8 in two ways: nul is encoded as `C0 80`, and supplementary characters are encoded as a surrogate pair.
+/// Each of these can be switched off, which covers the closely related variants:
+///
+/// | Variant    | `escape_nul` | `surrogate_pairs` |
+/// |------------|--------------|-------------------|
+/// | MUTF-8     | `true`       | `true`            |
+/// | CESU-8     | `false`      | `true`            |
+/// | "Nul-only" | `true`       | `false`           |
+///
+/// `EncodeOptions::new()` starts out as MUTF-8, the same as `encode`.
+///
+/// ```
+/// use mutf8::EncodeOptions;
+///
+/// let data = EncodeOptions::cesu8().encode("\0\u{1F600}");
+/// assert_eq!(&*data, b"\0\xED\xA0\xBD\xED\xB8\x80");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodeOptions {
+	pub(crate) escape_nul: bool,
+	pub(crate) surrogate_pairs: bool,
+}
+
+impl EncodeOptions {
+	/// Creates an encoder that produces MUTF-8, the same as `encode`.
+	pub const fn new() -> Self {
+		EncodeOptions {
+			escape_nul: true,
+			surrogate_pairs: true,
+		}
+	}
+
+	/// Creates an encoder that produces CESU-8.
+	///
+	/// This is MUTF-8, without the special encoding for nul.
+	pub const fn cesu8() -> Self {
+		Self::new().escape_nul(false)
+	}
+
+	/// Sets whether nul is encoded as `C0 80`, rather than a raw nul byte.
+	pub const fn escape_nul(mut self, escape: bool) -> Self {
+		self.escape_nul = escape;
+		self
+	}
+
+	/// Sets whether supplementary characters are encoded as a surrogate pair (six bytes), rather than their four byte UTF-8 encoding.
+	pub const fn surrogate_pairs(mut self, pairs: bool) -> Self {
+		self.surrogate_pairs = pairs;
+		self
+	}
+
+	/// Converts the input, following these options.
+	///
+	/// The input is borrowed if nothing needed to be encoded differently.
+	pub fn encode<'a>(&self, input: &'a str) -> Cow<'a, [u8]> {
+		encode_with(input, self)
+	}
+}
+
+impl Default for EncodeOptions {
+	fn default() -> Self {
+		Self::new()
+	}
+}
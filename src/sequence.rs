@@ -0,0 +1,129 @@
+//! Tokenizing MUTF-8 into the sequences it's made of.
+
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+use crate::error::ErrorKind;
+use crate::mutf8::{next_unit, Unit};
+use crate::options::DecodeOptions;
+
+/// A single sequence of MUTF-8, classified by how it's encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Sequence<'a> {
+	/// A one byte encoding.
+	Ascii(u8),
+	/// A two byte encoding, other than an encoded nul.
+	TwoByte(char),
+	/// Nul, encoded as `C0 80`.
+	EncodedNul,
+	/// A three byte encoding, other than a surrogate.
+	ThreeByte(char),
+	/// A supplementary character, encoded as a surrogate pair. (Two three byte encodings)
+	SurrogatePair(char),
+	/// A surrogate that isn't part of a pair.
+	LoneSurrogate(u16),
+	/// Bytes that aren't valid MUTF-8.
+	///
+	/// These are the bytes `Error::error_len` covers, or the rest of the input if it ended partway through a sequence.
+	Invalid {
+		/// The offset of the bytes in the input.
+		offset: usize,
+		/// The invalid bytes.
+		bytes: &'a [u8],
+		/// What's wrong with them.
+		kind: ErrorKind,
+	},
+}
+
+/// An iterator over the sequences of MUTF-8 input, and their byte ranges.
+///
+/// Sequences are classified by the same strict rules as `mutf8_to_utf8`, so anything it rejects is yielded as `Sequence::Invalid`.
+/// (Except for lone surrogates, which are legal MUTF-8)
+///
+/// Created by `sequences` or `mstr::sequences`.
+#[derive(Debug, Clone)]
+pub struct Sequences<'a> {
+	input: &'a [u8],
+	offset: usize,
+}
+
+/// Returns an iterator over the sequences of the input, and their byte ranges.
+///
+/// ```
+/// use mutf8::{sequences, Sequence};
+///
+/// let mut iter = sequences(b"a\xC0\x80\xFF");
+/// assert_eq!(iter.next(), Some((0..1, Sequence::Ascii(b'a'))));
+/// assert_eq!(iter.next(), Some((1..3, Sequence::EncodedNul)));
+/// assert!(matches!(iter.next(), Some((_, Sequence::Invalid { offset: 3, .. }))));
+/// assert_eq!(iter.next(), None);
+/// ```
+pub fn sequences(input: &[u8]) -> Sequences<'_> {
+	Sequences {
+		input,
+		offset: 0,
+	}
+}
+
+impl<'a> Iterator for Sequences<'a> {
+	type Item = (Range<usize>, Sequence<'a>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let input = self.input;
+		let i = self.offset;
+		if i >= input.len() {
+			return None;
+		}
+
+		let (sequence, width) = match next_unit(input, i, &DecodeOptions::new()) {
+			Ok(Unit::Same(1)) => (Sequence::Ascii(input[i]), 1),
+			Ok(Unit::Same(2)) => (Sequence::TwoByte(decode_char(&input[i..i + 2])), 2),
+			Ok(Unit::Same(width)) => (Sequence::ThreeByte(decode_char(&input[i..i + width])), width),
+			Ok(Unit::Char('\0', width)) => (Sequence::EncodedNul, width),
+			Ok(Unit::Char(c, width)) => (Sequence::SurrogatePair(c), width),
+			Ok(Unit::Surrogate) => (Sequence::LoneSurrogate(decode_bits(&input[i..i + 3]) as u16), 3),
+			Err(error) => {
+				let width = error.error_len().unwrap_or(input.len() - i);
+				let sequence = Sequence::Invalid {
+					offset: i,
+					bytes: &input[i..i + width],
+					kind: error.kind().clone(),
+				};
+				(sequence, width)
+			}
+		};
+
+		self.offset += width;
+		Some((i..i + width, sequence))
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		// A sequence takes between one and six bytes.
+		let len = self.input.len() - self.offset;
+		(len.div_ceil(6), Some(len))
+	}
+}
+
+impl FusedIterator for Sequences<'_> {
+}
+
+/// Decodes the bits of a two or three byte encoding.
+#[inline]
+fn decode_bits(bytes: &[u8]) -> u32 {
+	match *bytes {
+		// Bits in: 110xxxxx 10xxxxxx
+		[byte1, byte2] => ((byte1 as u32 & 0x1F) << 6) | (byte2 as u32 & 0x3F),
+		// Bits in: 1110xxxx 10xxxxxx 10xxxxxx
+		[byte1, byte2, byte3] => ((byte1 as u32 & 0x0F) << 12) | ((byte2 as u32 & 0x3F) << 6) | (byte3 as u32 & 0x3F),
+		_ => unreachable!("only two and three byte encodings are decoded"),
+	}
+}
+
+/// Decodes a two or three byte encoding that's the same as in UTF-8.
+#[inline]
+fn decode_char(bytes: &[u8]) -> char {
+	// safety: The sequence was validated, and isn't a surrogate.
+	unsafe { std::char::from_u32_unchecked(decode_bits(bytes)) }
+}
@@ -1,15 +1,29 @@
 use std::borrow::{Borrow, Cow, ToOwned};
 use std::convert::TryFrom;
 use std::fmt::{Debug, Display, Formatter, Result as FResult};
-use std::ops::Deref;
+use std::ops::{Deref, DerefMut, Index};
 
 #[cfg(feature = "serde")]
 use serde::{de::SeqAccess, Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::ascii::is_ascii;
+use crate::chunks::{chunks, Chunks};
 use crate::error::{Result as MResult, Error as MError};
+use crate::index::MstrIndex;
+use crate::iter::{CharIndices, Chars, EncodeUtf16};
+use crate::decode;
+use crate::encode;
 use crate::mutf8_to_utf8;
+use crate::mutf8_to_utf8_in_place;
+use crate::mutf8_to_utf8_lossy;
+use crate::mutf8::{decode_to, decoded_len, is_continuation, validate_with, MODE_COPY};
+use crate::options::{DecodeOptions, Surrogates};
+use crate::sequence::{sequences, Sequences};
 use crate::utf8_to_mutf8;
 
+/// An owned MUTF-8 string.
+///
+/// Just like `mstr`, this is always valid MUTF-8.
 #[derive(Eq, PartialEq, Hash, Clone)]
 pub struct MString {
 	inner: Box<[u8]>,
@@ -24,14 +38,65 @@ impl MString {
 		Ok(MString { inner: boxed_data })
 	}
 
-	pub fn from_mutf8(input: impl Into<Box<[u8]>>) -> MString {
+	/// Converts UTF-8 bytes into MUTF-8, replacing anything invalid with U+FFFD REPLACEMENT CHARACTER.
+	///
+	/// See `String::from_utf8_lossy`.
+	pub fn from_utf8_lossy(input: &[u8]) -> MString {
+		let utf8 = String::from_utf8_lossy(input);
+		let boxed_data = match encode(&utf8) {
+			Cow::Borrowed(data) => data.into(),
+			Cow::Owned(data) => data.into_boxed_slice(),
+		};
+		MString { inner: boxed_data }
+	}
+
+	/// Creates a string from UTF-16 code units, such as the contents of a Java `char[]`.
+	///
+	/// Every sequence of code units can be encoded, so lone surrogates are kept as is.
+	pub fn from_utf16(input: &[u16]) -> MString {
+		let len = input.iter()
+			.map(|&code| utf16_width(code))
+			.sum();
+
+		let mut data = Vec::with_capacity(len);
+		for &code in input {
+			push_code(&mut data, code);
+		}
+
+		MString {
+			inner: data.into_boxed_slice(),
+		}
+	}
+
+	/// Creates a string from MUTF-8 bytes, checking that they're valid.
+	///
+	/// See `mstr::from_mutf8` for what's considered valid.
+	pub fn from_mutf8(input: impl Into<Box<[u8]>>) -> MResult<MString> {
+		let inner = input.into();
+		validate(&inner)?;
+		Ok(MString {
+			inner,
+		})
+	}
+
+	/// Creates a string from MUTF-8 bytes, without checking that they're valid.
+	///
+	/// # Safety
+	///
+	/// The bytes have to be valid MUTF-8. (See `mstr::from_mutf8`)
+	pub unsafe fn from_mutf8_unchecked(input: impl Into<Box<[u8]>>) -> MString {
 		MString {
 			inner: input.into(),
 		}
 	}
 
 	pub fn into_string(self) -> MResult<String> {
-		Ok(String::from_utf8(self.into_utf8_bytes()?)?)
+		let bytes = self.into_utf8_bytes()?;
+
+		// safety: The strict decoder only ever produces valid UTF-8.
+		unsafe {
+			Ok(String::from_utf8_unchecked(bytes))
+		}
 	}
 
 	#[inline]
@@ -44,20 +109,26 @@ impl MString {
 		self.into_inner()
 	}
 
+	/// Converts the string into UTF-8, reusing its allocation.
 	pub fn into_utf8_bytes(self) -> MResult<Vec<u8>> {
-		let bytes = self.into_inner();
-		Ok(match mutf8_to_utf8(&bytes)? {
-			Cow::Borrowed(_data) => bytes.into_vec(),
-			Cow::Owned(data) => data,
-		})
+		let mut bytes = self.into_inner().into_vec();
+		let len = mutf8_to_utf8_in_place(&mut bytes)?;
+		bytes.truncate(len);
+		Ok(bytes)
 	}
 
+	/// Converts the string into UTF-8, reusing its allocation.
+	///
+	/// If the UTF-8 is shorter, the allocation is shrunk to fit.
 	pub fn into_boxed_utf8_bytes(self) -> MResult<Box<[u8]>> {
-		let bytes = self.into_inner();
-		Ok(match mutf8_to_utf8(&bytes)? {
-			Cow::Borrowed(_data) => bytes,
-			Cow::Owned(data) => data.into_boxed_slice(),
-		})
+		let mut bytes = self.into_inner();
+		let len = mutf8_to_utf8_in_place(&mut bytes)?;
+		if len == bytes.len() {
+			return Ok(bytes);
+		}
+		let mut bytes = bytes.into_vec();
+		bytes.truncate(len);
+		Ok(bytes.into_boxed_slice())
 	}
 
 	pub fn as_mstr(&self) -> &mstr {
@@ -66,9 +137,8 @@ impl MString {
 
 	pub fn into_boxed_str(self) -> MResult<Box<str>> {
 		let bytes = self.into_boxed_utf8_bytes()?;
-		let _ = std::str::from_utf8(&bytes)?;
 
-		// safety: We check if the data is valid UTF8 above.
+		// safety: The strict decoder only ever produces valid UTF-8.
 		unsafe {
 			Ok(std::str::from_boxed_utf8_unchecked(bytes))
 		}
@@ -77,7 +147,7 @@ impl MString {
 	pub fn into_boxed_mstr(self) -> Box<mstr> {
 		let boxed = self.into_inner();
 
-		// safety: Any conversion operation on mstr is checked, just as MString is checked.
+		// safety: MString and mstr hold the same invariant.
 		unsafe {
 			Box::from_raw(Box::into_raw(boxed) as *mut mstr)
 		}
@@ -100,7 +170,7 @@ impl MString {
 		&self.inner
 	}
 
-	pub fn as_utf8_bytes(&self) -> MResult<Cow<[u8]>> {
+	pub fn as_utf8_bytes(&self) -> MResult<Cow<'_, [u8]>> {
 		mutf8_to_utf8(&self.inner)
 	}
 }
@@ -127,7 +197,7 @@ impl<'de> serde::de::Visitor<'de> for MStringVisitor {
 		where
 			E: serde::de::Error,
 	{
-		Ok(MString::from_mutf8(v))
+		MString::from_mutf8(v).map_err(E::custom)
 	}
 
 	fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
@@ -138,7 +208,7 @@ impl<'de> serde::de::Visitor<'de> for MStringVisitor {
 		while let Some(val) = seq.next_element::<u8>()? {
 			data.push(val);
 		}
-		Ok(MString::from_mutf8(data))
+		MString::from_mutf8(data).map_err(serde::de::Error::custom)
 	}
 }
 
@@ -159,7 +229,19 @@ impl Deref for MString {
 	type Target = mstr;
 
 	fn deref(&self) -> &<Self as Deref>::Target {
-		mstr::from_mutf8(&self.inner)
+		// safety: MString and mstr hold the same invariant.
+		unsafe {
+			mstr::from_mutf8_unchecked(&self.inner)
+		}
+	}
+}
+
+impl DerefMut for MString {
+	fn deref_mut(&mut self) -> &mut <Self as Deref>::Target {
+		// safety: MString and mstr hold the same invariant.
+		unsafe {
+			mstr::from_mutf8_unchecked_mut(&mut self.inner)
+		}
 	}
 }
 
@@ -184,6 +266,9 @@ impl TryFrom<MString> for Vec<u8> {
 	}
 }
 
+/// A borrowed MUTF-8 string.
+///
+/// This is always valid MUTF-8, which includes lone surrogates, as Java strings can contain them.
 #[derive(Eq, PartialEq, Hash)]
 #[allow(non_camel_case_types)]
 pub struct mstr {
@@ -191,14 +276,15 @@ pub struct mstr {
 }
 
 impl mstr {
-	pub fn from_utf8(bytes: &[u8]) -> MResult<Cow<mstr>> {
+	pub fn from_utf8(bytes: &[u8]) -> MResult<Cow<'_, mstr>> {
+		// safety: The encoder only ever produces valid MUTF-8.
 		let cow = match utf8_to_mutf8(bytes)? {
 			Cow::Borrowed(data) => {
-				let data = mstr::from_mutf8(data);
+				let data = unsafe { mstr::from_mutf8_unchecked(data) };
 				Cow::Borrowed(data)
 			}
 			Cow::Owned(data) => {
-				let data = MString::from_mutf8(data);
+				let data = unsafe { MString::from_mutf8_unchecked(data) };
 				Cow::Owned(data)
 			}
 		};
@@ -206,13 +292,38 @@ impl mstr {
 		Ok(cow)
 	}
 
-	pub fn from_mutf8(bytes: &[u8]) -> &mstr {
-		// Safety: all conversions that go from mstr/MString to a utf8 string return an error if the bytes are invalid utf8.
+	/// Creates a string from MUTF-8 bytes, checking that they're valid.
+	///
+	/// Valid MUTF-8 follows the same strict rules as `mutf8_to_utf8`, except that lone surrogates are allowed.
+	pub fn from_mutf8(bytes: &[u8]) -> MResult<&mstr> {
+		validate(bytes)?;
+
+		// safety: Validated above.
 		unsafe {
-			&*(bytes as *const [u8] as *const mstr)
+			Ok(mstr::from_mutf8_unchecked(bytes))
 		}
 	}
 
+	/// Creates a string from MUTF-8 bytes, without checking that they're valid.
+	///
+	/// # Safety
+	///
+	/// The bytes have to be valid MUTF-8. (See `mstr::from_mutf8`)
+	#[inline]
+	pub unsafe fn from_mutf8_unchecked(bytes: &[u8]) -> &mstr {
+		&*(bytes as *const [u8] as *const mstr)
+	}
+
+	/// Creates a mutable string from MUTF-8 bytes, without checking that they're valid.
+	///
+	/// # Safety
+	///
+	/// The bytes have to be valid MUTF-8. (See `mstr::from_mutf8`)
+	#[inline]
+	pub unsafe fn from_mutf8_unchecked_mut(bytes: &mut [u8]) -> &mut mstr {
+		&mut *(bytes as *mut [u8] as *mut mstr)
+	}
+
 	/// Returns the length of the string, in bytes.
 	#[inline]
 	pub fn len(&self) -> usize {
@@ -232,32 +343,189 @@ impl mstr {
 	}
 
 	/// Returns a mutable reference to the internal byte slice.
+	///
+	/// # Safety
+	///
+	/// The bytes have to still be valid MUTF-8 once the borrow ends. (See `mstr::from_mutf8`)
 	#[inline]
-	pub fn as_mut_bytes(&mut self) -> &mut [u8] {
+	pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
 		&mut self.bytes
 	}
 
+	/// Converts the string to its ASCII upper case equivalent in place.
+	///
+	/// Anything that isn't ASCII is left untouched.
+	#[inline]
+	pub fn make_ascii_uppercase(&mut self) {
+		// Bytes of a multi-byte encoding are never ASCII, so they aren't changed.
+		self.bytes.make_ascii_uppercase()
+	}
+
+	/// Converts the string to its ASCII lower case equivalent in place.
+	///
+	/// Anything that isn't ASCII is left untouched.
+	#[inline]
+	pub fn make_ascii_lowercase(&mut self) {
+		// Bytes of a multi-byte encoding are never ASCII, so they aren't changed.
+		self.bytes.make_ascii_lowercase()
+	}
+
+	/// Replaces every occurrence of the ASCII byte `from` with the ASCII byte `to`, in place.
+	///
+	/// # Panics
+	///
+	/// Panics if either byte isn't ASCII, or is nul, as nul is encoded as two bytes.
+	pub fn replace_ascii(&mut self, from: u8, to: u8) {
+		assert!(is_ascii(from), "{:#04X} isn't a non-nul ASCII byte", from);
+		assert!(is_ascii(to), "{:#04X} isn't a non-nul ASCII byte", to);
+
+		for byte in self.bytes.iter_mut() {
+			if *byte == from {
+				*byte = to;
+			}
+		}
+	}
+
 	/// Returns a raw pointer to the contained buffer.
 	#[inline]
 	pub fn as_ptr(&self) -> *const u8 {
 		self.bytes.as_ptr()
 	}
 
-	pub fn to_str(&self) -> MResult<Cow<str>> {
-		self.to_utf8()
+	/// Returns an iterator over the characters of the string.
+	///
+	/// The characters are decoded as they're needed, lone surrogates are yielded as U+FFFD REPLACEMENT CHARACTER.
+	#[inline]
+	pub fn chars(&self) -> Chars<'_> {
+		Chars::new(self)
 	}
 
-	pub fn to_utf8(&self) -> MResult<Cow<str>> {
-		let input = &self.bytes;
+	/// Returns an iterator over the characters of the string, and their byte offsets.
+	///
+	/// The offsets are into the MUTF-8 bytes, so they can be used to slice the string.
+	#[inline]
+	pub fn char_indices(&self) -> CharIndices<'_> {
+		CharIndices::new(self)
+	}
 
-		// @FIXME Jezza - 01 Jan. 2019: Eh, I don't know if I like this solution...
-		// I like separating all of the mutf8 -> utf8 code, but destructuring the Cow like this...
-		let data = match mutf8_to_utf8(input)? {
-			Cow::Borrowed(data) => Cow::Borrowed(std::str::from_utf8(data)?),
-			Cow::Owned(data) => Cow::Owned(String::from_utf8(data)?),
-		};
+	/// Returns an iterator over the sequences of the string, and their byte ranges.
+	///
+	/// This shows how the string is encoded, rather than what it decodes to.
+	/// As the string is always valid MUTF-8, `Sequence::Invalid` is never yielded.
+	#[inline]
+	pub fn sequences(&self) -> Sequences<'_> {
+		sequences(&self.bytes)
+	}
+
+	/// Returns an iterator over the chunks of the string that can, and can't, be decoded into UTF-8.
+	///
+	/// As the string is always valid MUTF-8, the invalid part of a chunk is always a lone surrogate.
+	#[inline]
+	pub fn chunks(&self) -> Chunks<'_> {
+		chunks(&self.bytes)
+	}
+
+	/// Returns an iterator over the UTF-16 code units of the string.
+	///
+	/// These are the Java `char`s the string was encoded from, lone surrogates included.
+	#[inline]
+	pub fn encode_utf16(&self) -> EncodeUtf16<'_> {
+		EncodeUtf16::new(self)
+	}
+
+	/// Returns the length of the string in UTF-16 code units.
+	///
+	/// This is what Java's `String.length()` returns.
+	pub fn utf16_len(&self) -> usize {
+		// Every code unit is encoded as its own sequence, so it's the same as the number of lead bytes.
+		self.bytes.iter()
+			.filter(|&&byte| !is_continuation(byte))
+			.count()
+	}
+
+	/// Returns the number of characters in the string.
+	///
+	/// A surrogate pair counts as one character, a lone surrogate counts as one as well. (See `mstr::chars`)
+	pub fn char_count(&self) -> usize {
+		self.chars().count()
+	}
 
-		Ok(data)
+	/// Converts an index in UTF-16 code units into a byte offset.
+	///
+	/// Returns `None` if the index is greater than `utf16_len`.
+	/// An index equal to `utf16_len` is the length of the string.
+	pub fn utf16_index_to_offset(&self, index: usize) -> Option<usize> {
+		let mut units = 0;
+		for (offset, &byte) in self.bytes.iter().enumerate() {
+			if is_continuation(byte) {
+				continue;
+			}
+			if units == index {
+				return Some(offset);
+			}
+			units += 1;
+		}
+		if units == index {
+			Some(self.bytes.len())
+		} else {
+			None
+		}
+	}
+
+	/// Converts a byte offset into an index in UTF-16 code units.
+	///
+	/// Returns `None` if the offset is greater than the length of the string, or lands in the middle of a sequence.
+	/// The offset may land between the two halves of a surrogate pair, as each half is its own code unit.
+	pub fn offset_to_utf16_index(&self, offset: usize) -> Option<usize> {
+		let head = self.bytes.get(..offset)?;
+		if self.bytes.get(offset).is_some_and(|&byte| is_continuation(byte)) {
+			return None;
+		}
+		Some(head.iter()
+			.filter(|&&byte| !is_continuation(byte))
+			.count())
+	}
+
+	/// Returns the part of the string between two indices in UTF-16 code units.
+	///
+	/// This is the same region JNI's `GetStringUTFRegion` copies, so it may split a surrogate pair, leaving a lone surrogate at either end.
+	///
+	/// # Panics
+	///
+	/// Panics if `start` is greater than `end`, or `end` is greater than `utf16_len`.
+	pub fn substring_utf16(&self, start: usize, end: usize) -> &mstr {
+		assert!(start <= end, "UTF-16 index {} is greater than {}", start, end);
+		let start_offset = self.utf16_index_to_offset(start)
+			.unwrap_or_else(|| panic!("UTF-16 index {} is out of range for a string of length {}", start, self.utf16_len()));
+		let end_offset = self.utf16_index_to_offset(end)
+			.unwrap_or_else(|| panic!("UTF-16 index {} is out of range for a string of length {}", end, self.utf16_len()));
+
+		// safety: Both offsets are at the start of a sequence, so the slice is still valid MUTF-8.
+		unsafe {
+			mstr::from_mutf8_unchecked(&self.bytes[start_offset..end_offset])
+		}
+	}
+
+	/// Converts the string into UTF-8.
+	///
+	/// As the string is always valid MUTF-8, the only thing that can't be converted is a lone surrogate.
+	/// Those are replaced with U+FFFD REPLACEMENT CHARACTER, use `to_utf8` to reject them instead.
+	pub fn to_str(&self) -> Cow<'_, str> {
+		self.to_utf8_lossy()
+	}
+
+	/// Converts the string into UTF-8.
+	///
+	/// As the string is always valid MUTF-8, this only fails if it contains a lone surrogate.
+	pub fn to_utf8(&self) -> MResult<Cow<'_, str>> {
+		decode(&self.bytes)
+	}
+
+	/// Converts the string into UTF-8, replacing lone surrogates with U+FFFD REPLACEMENT CHARACTER.
+	///
+	/// See `mutf8_to_utf8_lossy`.
+	pub fn to_utf8_lossy(&self) -> Cow<'_, str> {
+		mutf8_to_utf8_lossy(&self.bytes)
 	}
 
 	pub fn into_m_string(self: Box<mstr>) -> MString {
@@ -270,40 +538,79 @@ impl mstr {
 		}
 	}
 
-	/// Returns the byte at the given index.
+	/// Returns the byte at the given index, or a slice of the string for a range.
 	///
-	/// Returns `None` if `idx` is greater than or equal to the string length.
+	/// Returns `None` if the index is out of bounds, or if the range doesn't start and end on a char boundary.
 	#[inline]
-	pub fn get(&self, idx: usize) -> Option<&u8> {
-		// TODO: Use SliceIndex when it becomes stable
-		self.bytes.get(idx)
+	pub fn get<I: MstrIndex>(&self, idx: I) -> Option<&I::Output> {
+		idx.get(self)
 	}
 
-	/// Returns the byte at the given index, bypassing bounds-checking.
+	/// Returns the byte at the given index, or a slice of the string for a range, bypassing any checks.
 	///
 	/// # Safety
 	///
-	/// The caller of this function must guarantee that `idx` is less than
-	/// the string length.
-	pub unsafe fn get_unchecked(&self, idx: usize) -> &u8 {
-		// TODO: Use SliceIndex when it becomes stable
-		self.bytes.get_unchecked(idx)
+	/// The caller of this function must guarantee that the index is in bounds,
+	/// and that a range starts and ends on a char boundary.
+	#[inline]
+	pub unsafe fn get_unchecked<I: MstrIndex>(&self, idx: I) -> &I::Output {
+		idx.get_unchecked(self)
 	}
 
-	/// Returns a borrowed reference to the first byte in the string.
+	/// Returns whether the byte at the given index is the start of a character, or the end of the string.
 	///
-	/// Returns `None` if the string is empty.
-	#[inline]
-	pub fn first(&self) -> Option<&u8> {
-		self.bytes.first()
+	/// The two halves of a surrogate pair make up one character, so the middle of a pair isn't a boundary.
+	/// Lone surrogates are characters of their own.
+	pub fn is_char_boundary(&self, index: usize) -> bool {
+		if index == 0 || index == self.bytes.len() {
+			return true;
+		}
+		match self.bytes.get(index) {
+			None => false,
+			Some(&byte) if is_continuation(byte) => false,
+			// A low surrogate that follows a high surrogate is the second half of a pair.
+			Some(0xED) => !matches!(
+				(self.bytes.get(index + 1), self.bytes.get(index.wrapping_sub(3)..index)),
+				(Some(0xB0..=0xBF), Some(&[0xED, 0xA0..=0xAF, _]))
+			),
+			Some(_) => true,
+		}
 	}
 
-	/// Returns a mutable reference to the first byte in the string.
+	/// Returns the closest char boundary at or below the given index.
+	///
+	/// An index past the end of the string is clamped to its length.
+	pub fn floor_char_boundary(&self, index: usize) -> usize {
+		if index >= self.bytes.len() {
+			return self.bytes.len();
+		}
+		let mut index = index;
+		while !self.is_char_boundary(index) {
+			index -= 1;
+		}
+		index
+	}
+
+	/// Returns the closest char boundary at or above the given index.
+	///
+	/// An index past the end of the string is clamped to its length.
+	pub fn ceil_char_boundary(&self, index: usize) -> usize {
+		if index >= self.bytes.len() {
+			return self.bytes.len();
+		}
+		let mut index = index;
+		while !self.is_char_boundary(index) {
+			index += 1;
+		}
+		index
+	}
+
+	/// Returns a borrowed reference to the first byte in the string.
 	///
 	/// Returns `None` if the string is empty.
 	#[inline]
-	pub fn first_mut(&mut self) -> Option<&mut u8> {
-		self.bytes.first_mut()
+	pub fn first(&self) -> Option<&u8> {
+		self.bytes.first()
 	}
 
 	/// Returns a borrowed reference to the last byte in the string.
@@ -313,13 +620,14 @@ impl mstr {
 	pub fn last(&self) -> Option<&u8> {
 		self.bytes.last()
 	}
+}
+
+impl<I: MstrIndex> Index<I> for mstr {
+	type Output = I::Output;
 
-	/// Returns a mutable reference to the last byte in the string.
-	///
-	/// Returns `None` if the string is empty.
 	#[inline]
-	pub fn last_mut(&mut self) -> Option<&mut u8> {
-		self.bytes.last_mut()
+	fn index(&self, index: I) -> &I::Output {
+		index.index(self)
 	}
 }
 
@@ -353,7 +661,10 @@ impl ToOwned for mstr {
 	type Owned = MString;
 
 	fn to_owned(&self) -> MString {
-		MString::from_mutf8(&self.bytes)
+		// safety: MString and mstr hold the same invariant.
+		unsafe {
+			MString::from_mutf8_unchecked(&self.bytes)
+		}
 	}
 }
 
@@ -373,14 +684,64 @@ impl AsRef<mstr> for MString {
 
 impl Debug for mstr {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
-		let lossy = std::string::String::from_utf8_lossy(self.as_bytes());
-		Debug::fmt(&lossy, f)
+		Debug::fmt(&self.to_utf8_lossy(), f)
 	}
 }
 
 impl Display for mstr {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
-		let lossy = std::string::String::from_utf8_lossy(self.as_bytes());
-		Display::fmt(&lossy, f)
+		Display::fmt(&self.to_utf8_lossy(), f)
 	}
-}
\ No newline at end of file
+}
+
+/// Returns the length of the string once it's decoded as UTF-8, in bytes.
+///
+/// This is the length `mstr::to_str` produces, which is also what `mstr::to_utf8` produces when it succeeds.
+pub fn utf8_len(input: &mstr) -> usize {
+	let options = DecodeOptions::new().surrogates(Surrogates::Replace);
+	decoded_len(input.as_bytes(), 0, &options).expect("mstr holds valid MUTF-8")
+}
+
+/// Returns the number of bytes a UTF-16 code unit is encoded as.
+#[inline]
+fn utf16_width(code: u16) -> usize {
+	match code {
+		0x0001..=0x007F => 1,
+		0x0000 | 0x0080..=0x07FF => 2,
+		_ => 3,
+	}
+}
+
+/// Encodes a single UTF-16 code unit, each half of a surrogate pair is encoded separately.
+#[inline]
+fn push_code(data: &mut Vec<u8>, code: u16) {
+	match utf16_width(code) {
+		1 => data.push(code as u8),
+		// Bits out: 110xxxxx 10xxxxxx
+		2 => data.extend(&[0xC0 | (code >> 6) as u8, 0x80 | (code & 0x3F) as u8]),
+		// Bits out: 1110xxxx 10xxxxxx 10xxxxxx
+		_ => data.extend(&[0xE0 | (code >> 12) as u8, 0x80 | ((code >> 6) & 0x3F) as u8, 0x80 | (code & 0x3F) as u8]),
+	}
+}
+
+/// Checks the invariant that `mstr` and `MString` hold.
+fn validate(bytes: &[u8]) -> MResult<()> {
+	validate_with(bytes, &DecodeOptions::new().surrogates(Surrogates::Wtf8))
+}
+
+/// Decodes the string, appending it to `output`.
+///
+/// This doesn't allocate anything besides growing `output`.
+/// On error, `output` is left as it was.
+pub fn decode_into(input: &mstr, output: &mut String) -> MResult<()> {
+	let start = output.len();
+	output.reserve(input.len());
+
+	// safety: The strict decoder only ever writes valid UTF-8, and anything written is removed on error.
+	let data = unsafe { output.as_mut_vec() };
+	if let Err(err) = decode_to(input.as_bytes(), &DecodeOptions::new(), data, MODE_COPY) {
+		data.truncate(start);
+		return Err(err);
+	}
+	Ok(())
+}
@@ -0,0 +1,38 @@
+use std::borrow::Cow;
+
+use crate::error::Result;
+use crate::mutf8::{decode_with, encode_wtf8, validate_with};
+use crate::options::{DecodeOptions, EncodeOptions, Surrogates};
+
+/// Converts MUTF-8 into WTF-8.
+///
+/// This is lossless, unlike `mutf8_to_utf8`, as lone surrogates are kept.
+/// Every Java string can be round-tripped through `wtf8_to_mutf8`.
+///
+/// The output is always well-formed WTF-8.
+/// That is, it's UTF-8 except for lone surrogates, and a surrogate pair is always joined into a four byte encoding.
+/// If there are no lone surrogates, it's plain UTF-8.
+///
+/// The input is borrowed if it doesn't contain any encoded nul bytes or surrogate pairs.
+pub fn mutf8_to_wtf8(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+	decode_with(input, &DecodeOptions::new().surrogates(Surrogates::Wtf8))
+}
+
+/// Converts WTF-8 into MUTF-8.
+///
+/// The input is validated first, so an error is returned for anything that isn't UTF-8, except for surrogates.
+/// Two halves of a surrogate pair that were encoded separately are accepted, and end up as a pair in the output,
+/// even though that isn't well-formed WTF-8.
+///
+/// The input is borrowed if it doesn't contain any nul bytes or supplementary characters.
+pub fn wtf8_to_mutf8(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+	let options = DecodeOptions::new()
+		.allow_nul(true)
+		.allow_escaped_nul(false)
+		.allow_four_byte(true)
+		.surrogates(Surrogates::Wtf8);
+	validate_with(input, &options)?;
+
+	// safety: Validated as WTF-8 above.
+	Ok(unsafe { encode_wtf8(input, &EncodeOptions::new()) })
+}
@@ -0,0 +1,29 @@
+use mutf8::{decode, encode, mutf8_to_utf8_lossy};
+
+// Long enough to cover every word and vector width, with the interesting byte at every position.
+#[test]
+fn position_test() {
+	for len in 0..80 {
+		for at in 0..len {
+			let mut utf8 = "a".repeat(len);
+			utf8.replace_range(at..at + 1, "\0");
+			let mut mutf8 = utf8.clone().into_bytes();
+			mutf8.splice(at..at + 1, [0xC0, 0x80].iter().copied());
+
+			assert_eq!(&*encode(&utf8), &mutf8[..]);
+			assert_eq!(decode(&mutf8).expect("invalid input"), utf8);
+
+			let mut utf8 = "a".repeat(len);
+			utf8.replace_range(at..at + 1, "\u{E9}");
+			assert_eq!(&*encode(&utf8), utf8.as_bytes());
+			assert_eq!(decode(utf8.as_bytes()).expect("invalid input"), utf8);
+
+			let mut invalid = "a".repeat(len).into_bytes();
+			invalid[at] = 0;
+			assert_eq!(decode(&invalid).unwrap_err().valid_up_to(), at);
+			invalid[at] = 0xFF;
+			assert_eq!(decode(&invalid).unwrap_err().valid_up_to(), at);
+			assert_eq!(mutf8_to_utf8_lossy(&invalid).chars().position(|c| c == '\u{FFFD}'), Some(at));
+		}
+	}
+}
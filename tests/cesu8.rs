@@ -0,0 +1,33 @@
+use std::borrow::Cow;
+
+use mutf8::error::ErrorKind;
+use mutf8::{cesu8_to_utf8, utf8_to_cesu8};
+
+#[test]
+fn decode_test() {
+	let data = cesu8_to_utf8(b"a\0b").unwrap();
+	assert!(matches!(data, Cow::Borrowed(_)));
+
+	let data = cesu8_to_utf8(b"a\0\xED\xA0\xBD\xED\xB8\x80").unwrap();
+	assert!(matches!(data, Cow::Owned(_)));
+	assert_eq!(&*data, "a\0\u{1F600}".as_bytes());
+}
+
+#[test]
+fn decode_invalid_test() {
+	assert_eq!(cesu8_to_utf8(b"a\xC0\x80").unwrap_err().kind(), &ErrorKind::Overlong);
+	assert_eq!(cesu8_to_utf8(b"\xF0\x9F\x98\x80").unwrap_err().kind(), &ErrorKind::FourByte);
+	assert_eq!(cesu8_to_utf8(b"\xED\xA0\xBD").unwrap_err().kind(), &ErrorKind::LoneSurrogate);
+	assert_eq!(cesu8_to_utf8(b"\xED\xB8\x80").unwrap_err().kind(), &ErrorKind::LoneSurrogate);
+}
+
+#[test]
+fn encode_test() {
+	let data = utf8_to_cesu8(b"a\0b").unwrap();
+	assert!(matches!(data, Cow::Borrowed(_)));
+
+	let data = utf8_to_cesu8("a\0\u{1F600}".as_bytes()).unwrap();
+	assert_eq!(&*data, b"a\0\xED\xA0\xBD\xED\xB8\x80");
+
+	assert_eq!(utf8_to_cesu8(b"a\xFF").unwrap_err().valid_up_to(), 1);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "use-structs")]
+
+use mutf8::mstr;
+
+#[test]
+fn chars_test() {
+	let data = mstr::from_mutf8(b"a\xC0\x80\xC3\xA9\xE2\x82\xAC\xED\xA0\xBD\xED\xB8\x80").unwrap();
+
+	let chars: Vec<char> = data.chars().collect();
+	assert_eq!(chars, ['a', '\0', '\u{E9}', '\u{20AC}', '\u{1F600}']);
+
+	let chars: Vec<char> = data.chars().rev().collect();
+	assert_eq!(chars, ['\u{1F600}', '\u{20AC}', '\u{E9}', '\0', 'a']);
+
+	let mut chars = data.chars();
+	assert_eq!(chars.next(), Some('a'));
+	assert_eq!(chars.next_back(), Some('\u{1F600}'));
+	assert_eq!(chars.as_mstr().as_bytes(), b"\xC0\x80\xC3\xA9\xE2\x82\xAC");
+}
+
+#[test]
+fn char_indices_test() {
+	let data = mstr::from_mutf8(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80b").unwrap();
+
+	let indices: Vec<(usize, char)> = data.char_indices().collect();
+	assert_eq!(indices, [(0, 'a'), (1, '\0'), (3, '\u{1F600}'), (9, 'b')]);
+
+	let indices: Vec<(usize, char)> = data.char_indices().rev().collect();
+	assert_eq!(indices, [(9, 'b'), (3, '\u{1F600}'), (1, '\0'), (0, 'a')]);
+}
+
+#[test]
+fn lone_surrogate_test() {
+	// A high surrogate, a pair, and a low surrogate.
+	let data = mstr::from_mutf8(b"\xED\xA0\xBD\xED\xA0\xBD\xED\xB8\x80\xED\xB8\x80").unwrap();
+
+	let forward: Vec<(usize, char)> = data.char_indices().collect();
+	assert_eq!(forward, [(0, '\u{FFFD}'), (3, '\u{1F600}'), (9, '\u{FFFD}')]);
+
+	let mut backward: Vec<(usize, char)> = data.char_indices().rev().collect();
+	backward.reverse();
+	assert_eq!(backward, forward);
+
+	assert_eq!(data.chars().collect::<String>(), data.to_str());
+}
@@ -0,0 +1,54 @@
+use mutf8::chunks;
+
+fn parts(input: &[u8]) -> Vec<(String, Vec<u8>)> {
+	chunks(input)
+		.map(|chunk| (chunk.valid().to_string(), chunk.invalid().to_vec()))
+		.collect()
+}
+
+#[test]
+fn chunks_test() {
+	assert!(parts(b"").is_empty());
+	assert_eq!(parts(b"value"), [("value".to_string(), vec![])]);
+	assert_eq!(parts(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80"), [("a\0\u{1F600}".to_string(), vec![])]);
+
+	assert_eq!(parts(b"a\xFFb\0\xC1\xBFc\xE2\x82"), [
+		("a".to_string(), vec![0xFF]),
+		("b".to_string(), vec![0x00]),
+		("".to_string(), vec![0xC1]),
+		("".to_string(), vec![0xBF]),
+		("c".to_string(), vec![0xE2, 0x82]),
+	]);
+
+	// Lone surrogates can't be decoded into UTF-8.
+	assert_eq!(parts(b"a\xED\xA0\xBDb"), [
+		("a".to_string(), vec![0xED, 0xA0, 0xBD]),
+		("b".to_string(), vec![]),
+	]);
+}
+
+#[test]
+fn borrow_test() {
+	use std::borrow::Cow;
+
+	let mut iter = chunks(b"java/lang\xFF\xC0\x80");
+	let chunk = iter.next().unwrap();
+	assert!(matches!(chunk.valid(), Cow::Borrowed("java/lang")));
+
+	let (valid, invalid) = iter.next().unwrap().into_parts();
+	assert!(matches!(valid, Cow::Owned(ref valid) if valid == "\0"));
+	assert!(invalid.is_empty());
+	assert!(iter.next().is_none());
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn mstr_test() {
+	use mutf8::mstr;
+
+	let data = mstr::from_mutf8(b"\xED\xB8\x80a\xC0\x80").unwrap();
+	let parts: Vec<_> = data.chunks().map(|chunk| chunk.into_parts()).collect();
+	assert_eq!(parts.len(), 2);
+	assert_eq!((&*parts[0].0, parts[0].1), ("", &b"\xED\xB8\x80"[..]));
+	assert_eq!((&*parts[1].0, parts[1].1), ("a\0", &b""[..]));
+}
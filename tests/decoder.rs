@@ -0,0 +1,74 @@
+use mutf8::error::{ErrorKind, Expected};
+use mutf8::{mutf8_to_utf8, Decoder};
+
+const INPUT: &[u8] = b"a\xC0\x80\xC3\xA9\xE2\x82\xAC\xED\xA0\xBD\xED\xB8\x80b";
+
+fn decode_in(input: &[u8], size: usize) -> mutf8::error::Result<String> {
+	let mut decoder = Decoder::new();
+	let mut output = String::new();
+	for chunk in input.chunks(size) {
+		decoder.decode_chunk(chunk, &mut output)?;
+	}
+	decoder.finish()?;
+	Ok(output)
+}
+
+#[test]
+fn split_test() {
+	for size in 1..=INPUT.len() {
+		assert_eq!(decode_in(INPUT, size).unwrap(), "a\0\u{E9}\u{20AC}\u{1F600}b", "chunk size {}", size);
+	}
+
+	// Every split point, with the surrogate pair split in between.
+	for mid in 0..=INPUT.len() {
+		let mut decoder = Decoder::new();
+		let mut output = String::new();
+		decoder.decode_chunk(&INPUT[..mid], &mut output).unwrap();
+		decoder.decode_chunk(&INPUT[mid..], &mut output).unwrap();
+		decoder.finish().unwrap();
+		assert_eq!(output, "a\0\u{E9}\u{20AC}\u{1F600}b");
+	}
+}
+
+#[test]
+fn pending_test() {
+	let mut decoder = Decoder::new();
+	let mut output = String::new();
+
+	assert_eq!(decoder.decode_chunk(b"ab\xED\xA0\xBD", &mut output).unwrap(), 2);
+	assert_eq!(decoder.decode_chunk(b"\xED\xB8", &mut output).unwrap(), 0);
+	assert_eq!(decoder.decode_chunk(b"\x80cdefghij", &mut output).unwrap(), 12);
+	assert_eq!(output, "ab\u{1F600}cdefghij");
+}
+
+#[test]
+fn error_test() {
+	let inputs: &[&[u8]] = &[
+		b"ab\xE2\x82", b"ab\xED\xA0\xBD", b"ab\xED\xA0\xBD\xED\xB8", b"ab\xED\xA0\xBDcd", b"abcdefgh\xFF", b"a\0",
+		// An incomplete sequence, followed by the start of another one.
+		b"ab\xE2\x82\xE0\x80", b"\xC3\xC3", b"ab\xED\xA0\xBD\xED\x41", b"ab\xED\xA0\xBD\xE2\x82\xAC",
+	];
+	for input in inputs {
+		let expected = mutf8_to_utf8(input).unwrap_err();
+		for size in 1..=input.len() {
+			assert_eq!(decode_in(input, size).unwrap_err(), expected, "{:?} in chunks of {}", input, size);
+		}
+	}
+}
+
+#[test]
+fn finish_test() {
+	let mut decoder = Decoder::new();
+	let mut output = String::new();
+
+	decoder.decode_chunk(b"abc\xE2\x82", &mut output).unwrap();
+	assert_eq!(output, "abc");
+	let error = decoder.finish().unwrap_err();
+	assert_eq!(error.kind(), &ErrorKind::UnexpectedEnd { expected: Expected::ThreeByte });
+	assert_eq!(error.valid_up_to(), 3);
+
+	// The decoder starts over once it's finished.
+	decoder.decode_chunk(b"\xC0\x80", &mut output).unwrap();
+	decoder.finish().unwrap();
+	assert_eq!(output, "abc\0");
+}
@@ -0,0 +1,53 @@
+use std::error::Error as _;
+use std::io::ErrorKind as IoErrorKind;
+
+use mutf8::error::{Error, ErrorKind};
+use mutf8::{mutf8_to_utf8, utf8_to_mutf8};
+
+#[test]
+fn display_test() {
+	let inputs: &[&[u8]] = &[
+		b"ab\0",
+		b"ab\x80",
+		b"ab\xC3a",
+		b"ab\xC1\xBF",
+		b"ab\xF0\x9F\x98\x80",
+		b"ab\xFF",
+		b"ab\xC3",
+		b"ab\xE2\x82",
+	];
+	for input in inputs {
+		let error = mutf8_to_utf8(input).unwrap_err();
+		let message = error.to_string();
+		assert!(message.contains("at byte 2"), "{}", message);
+	}
+
+	for input in [&b"ab\xFF"[..], b"ab\xE2\x82"] {
+		let error = utf8_to_mutf8(input).unwrap_err();
+		let message = error.to_string();
+		assert!(message.starts_with("Invalid UTF-8 input."), "{}", message);
+		assert!(message.contains("at byte 2"), "{}", message);
+	}
+}
+
+#[test]
+fn source_test() {
+	let error = utf8_to_mutf8(b"ab\xFF").unwrap_err();
+	let source = error.source().expect("missing source");
+	assert!(source.is::<std::str::Utf8Error>());
+
+	let error = mutf8_to_utf8(b"ab\xFF").unwrap_err();
+	assert!(error.source().is_none());
+}
+
+#[test]
+fn io_test() {
+	let error = mutf8_to_utf8(b"ab\0").unwrap_err();
+	let io_error: std::io::Error = error.clone().into();
+	assert_eq!(io_error.kind(), IoErrorKind::InvalidData);
+
+	let inner = io_error.into_inner().expect("missing inner error");
+	let inner = inner.downcast::<Error>().expect("wrong inner error");
+	assert_eq!(*inner, error);
+	assert_eq!(inner.kind(), &ErrorKind::Nul);
+}
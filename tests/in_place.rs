@@ -0,0 +1,33 @@
+use mutf8::mutf8_to_utf8_in_place;
+
+#[test]
+fn in_place_test() {
+	let mut data = b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80b\xC3\xA9".to_vec();
+	let len = mutf8_to_utf8_in_place(&mut data).unwrap();
+	data.truncate(len);
+	assert_eq!(data, "a\0\u{1F600}b\u{E9}".as_bytes());
+
+	let mut data = *b"value";
+	assert_eq!(mutf8_to_utf8_in_place(&mut data).unwrap(), 5);
+	assert_eq!(&data, b"value");
+}
+
+#[test]
+fn in_place_error_test() {
+	let mut data = *b"a\xC0\x80b\xED\xA0\xBD";
+	let error = mutf8_to_utf8_in_place(&mut data).unwrap_err();
+	assert_eq!(error.valid_up_to(), 4);
+	assert_eq!(&data, b"a\xC0\x80b\xED\xA0\xBD");
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn into_string_test() {
+	use mutf8::MString;
+
+	let string = MString::from_mutf8(&b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80"[..]).unwrap();
+	let ptr = string.as_mutf8_bytes().as_ptr();
+	let string = string.into_string().unwrap();
+	assert_eq!(string, "a\0\u{1F600}");
+	assert_eq!(string.as_ptr(), ptr);
+}
@@ -0,0 +1,40 @@
+use mutf8::{encode, encode_into, encode_to_slice};
+
+#[test]
+fn encode_into_test() {
+	let mut output = b"prefix ".to_vec();
+	encode_into("a\0\u{1F600}", &mut output);
+	encode_into(" value", &mut output);
+	assert_eq!(output, b"prefix a\xC0\x80\xED\xA0\xBD\xED\xB8\x80 value");
+}
+
+#[test]
+fn encode_to_slice_test() {
+	let input = "a\0\u{E9}\u{1F600}b";
+	let expected = encode(input);
+
+	let mut output = [0xFF; 32];
+	let written = encode_to_slice(input, &mut output).unwrap();
+	assert_eq!(&output[..written], &*expected);
+	assert!(output[written..].iter().all(|&b| b == 0xFF));
+
+	let mut output = [0xFF; 8];
+	let error = encode_to_slice(input, &mut output).unwrap_err();
+	assert_eq!(error.needed(), expected.len());
+	assert_eq!(output, [0xFF; 8]);
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn decode_into_test() {
+	use mutf8::{decode_into, mstr};
+
+	let mut output = String::from("prefix ");
+	decode_into(mstr::from_mutf8(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80").unwrap(), &mut output).unwrap();
+	decode_into(mstr::from_mutf8(b" value").unwrap(), &mut output).unwrap();
+	assert_eq!(output, "prefix a\0\u{1F600} value");
+
+	let error = decode_into(mstr::from_mutf8(b"b\xC0\x80\xED\xA0\xBD").unwrap(), &mut output).unwrap_err();
+	assert_eq!(error.valid_up_to(), 3);
+	assert_eq!(output, "prefix a\0\u{1F600} value");
+}
@@ -0,0 +1,39 @@
+use mutf8::{encode, mutf8_len};
+
+#[test]
+fn mutf8_len_test() {
+	let inputs = ["", "value", "a\0b", "\u{E9}\u{20AC}", "\u{1F600}", "a\0\u{1F600}\0"];
+	for input in inputs.iter() {
+		assert_eq!(mutf8_len(input), encode(input).len());
+	}
+	assert_eq!(mutf8_len("a\0\u{1F600}"), 9);
+}
+
+#[test]
+fn single_allocation_test() {
+	use mutf8::mutf8_to_utf8;
+	use std::borrow::Cow;
+
+	let input = "a\0\u{1F600}".repeat(100);
+	let encoded = encode(&input);
+	assert!(matches!(&encoded, Cow::Owned(data) if data.capacity() == data.len()));
+
+	// Decoding never makes the data longer, so the input's length is enough.
+	let data = mutf8_to_utf8(&encoded).unwrap();
+	assert!(matches!(&data, Cow::Owned(data) if data.len() == input.len() && data.capacity() == encoded.len()));
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn utf8_len_test() {
+	use mutf8::{mstr, utf8_len};
+
+	let inputs: &[&[u8]] = &[b"", b"value", b"a\xC0\x80b", b"\xED\xA0\xBD\xED\xB8\x80", b"\xC3\xA9\xE2\x82\xAC"];
+	for input in inputs {
+		let input = mstr::from_mutf8(input).unwrap();
+		assert_eq!(utf8_len(input), input.to_utf8().unwrap().len());
+	}
+
+	let input = mstr::from_mutf8(b"ab\xED\xA0\xBD").unwrap();
+	assert_eq!(utf8_len(input), input.to_str().len());
+}
@@ -0,0 +1,38 @@
+use std::borrow::Cow;
+
+use mutf8::mutf8_to_utf8_lossy;
+
+#[test]
+fn valid_test() {
+	let data = mutf8_to_utf8_lossy(b"value \xC3\xA9");
+	assert!(matches!(data, Cow::Borrowed(_)));
+	assert_eq!(data, "value \u{E9}");
+
+	let data = mutf8_to_utf8_lossy(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80");
+	assert_eq!(data, "a\0\u{1F600}");
+}
+
+#[test]
+fn invalid_test() {
+	assert_eq!(mutf8_to_utf8_lossy(b"a\0b"), "a\u{FFFD}b");
+	assert_eq!(mutf8_to_utf8_lossy(b"a\x80\x80b"), "a\u{FFFD}\u{FFFD}b");
+	assert_eq!(mutf8_to_utf8_lossy(b"a\xE2\x82b"), "a\u{FFFD}b");
+	assert_eq!(mutf8_to_utf8_lossy(b"a\xED\xA0\xBDb"), "a\u{FFFD}b");
+	assert_eq!(mutf8_to_utf8_lossy(b"a\xF0\x9F\x98\x80"), "a\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}");
+	assert_eq!(mutf8_to_utf8_lossy(b"a\xE2\x82"), "a\u{FFFD}");
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn mstr_test() {
+	use mutf8::{mstr, MString};
+
+	let data = mstr::from_mutf8(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80\xED\xA0\xBD").unwrap();
+	assert_eq!(data.to_utf8_lossy(), "a\0\u{1F600}\u{FFFD}");
+	assert_eq!(data.to_str(), "a\0\u{1F600}\u{FFFD}");
+	assert_eq!(data.to_string(), "a\0\u{1F600}\u{FFFD}");
+	assert_eq!(format!("{:?}", data), "\"a\\0\u{1F600}\u{FFFD}\"");
+
+	let data = MString::from_utf8_lossy(b"a\0\xFF");
+	assert_eq!(data.as_mutf8_bytes(), b"a\xC0\x80\xEF\xBF\xBD");
+}
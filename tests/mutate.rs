@@ -0,0 +1,36 @@
+#![cfg(feature = "use-structs")]
+
+use mutf8::MString;
+
+#[test]
+fn ascii_case_test() {
+	let mut data = MString::from_utf8(b"Hello, \0\xC3\xA9\xF0\x9F\x98\x80World").unwrap();
+
+	data.make_ascii_uppercase();
+	assert_eq!(data.to_str(), "HELLO, \0\u{E9}\u{1F600}WORLD");
+
+	data.make_ascii_lowercase();
+	assert_eq!(data.to_str(), "hello, \0\u{E9}\u{1F600}world");
+}
+
+#[test]
+fn replace_ascii_test() {
+	let mut data = MString::from_utf8(b"java/lang/\0String").unwrap();
+
+	data.replace_ascii(b'/', b'.');
+	assert_eq!(data.to_str(), "java.lang.\0String");
+}
+
+#[test]
+#[should_panic]
+fn replace_ascii_nul_test() {
+	let mut data = MString::from_utf8(b"a\0b").unwrap();
+	data.replace_ascii(b'a', 0);
+}
+
+#[test]
+#[should_panic]
+fn replace_ascii_non_ascii_test() {
+	let mut data = MString::from_utf8(b"a\xC3\xA9").unwrap();
+	data.replace_ascii(0xA9, b'a');
+}
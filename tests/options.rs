@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+
+use mutf8::error::ErrorKind;
+use mutf8::{DecodeOptions, EncodeOptions, Surrogates};
+
+#[test]
+fn strict_test() {
+	let options = DecodeOptions::new();
+	assert_eq!(options, DecodeOptions::default());
+	assert_eq!(options.decode(b"\0").unwrap_err().kind(), &ErrorKind::Nul);
+	assert_eq!(options.decode(b"\xF0\x9F\x98\x80").unwrap_err().kind(), &ErrorKind::FourByte);
+	assert_eq!(options.decode(b"\xC1\xBF").unwrap_err().kind(), &ErrorKind::Overlong);
+	assert_eq!(options.decode(b"\xED\xA0\xBD").unwrap_err().kind(), &ErrorKind::LoneSurrogate);
+}
+
+#[test]
+fn nul_test() {
+	let options = DecodeOptions::new().allow_nul(true);
+	let data = options.decode(b"a\0b\xC0\x80").unwrap();
+	assert_eq!(&*data, b"a\0b\0");
+}
+
+#[test]
+fn four_byte_test() {
+	let options = DecodeOptions::new().allow_four_byte(true);
+	let data = options.decode(b"\xF0\x9F\x98\x80\xED\xA0\xBD\xED\xB8\x80").unwrap();
+	assert_eq!(&*data, "\u{1F600}\u{1F600}".as_bytes());
+
+	assert_eq!(options.decode(b"\xF0\x8F\xBF\xBF").unwrap_err().kind(), &ErrorKind::Overlong);
+	assert_eq!(options.decode(b"\xF4\x90\x80\x80").unwrap_err().kind(), &ErrorKind::OutOfRange);
+	assert_eq!(options.decode(b"\xF0\x9F\x98").unwrap_err().error_len(), None);
+}
+
+#[test]
+fn surrogates_test() {
+	let input = b"a\xED\xA0\xBDb\xED\xB8\x80";
+
+	let error = DecodeOptions::new()
+		.surrogates(Surrogates::Error)
+		.decode(input)
+		.unwrap_err();
+	assert_eq!(error.kind(), &ErrorKind::LoneSurrogate);
+	assert_eq!(error.valid_up_to(), 1);
+	assert_eq!(error.error_len(), Some(3));
+
+	let data = DecodeOptions::new()
+		.surrogates(Surrogates::Replace)
+		.decode(input)
+		.unwrap();
+	assert_eq!(&*data, "a\u{FFFD}b\u{FFFD}".as_bytes());
+
+	let data = DecodeOptions::new()
+		.surrogates(Surrogates::Wtf8)
+		.decode(input)
+		.unwrap();
+	assert_eq!(&*data, &input[..]);
+}
+
+#[test]
+fn overlong_test() {
+	let options = DecodeOptions::new().allow_overlong(true);
+	let data = options.decode(b"\xC1\xBF\xE0\x81\x81\xE0\x82\xA9\xC0\x80").unwrap();
+	assert_eq!(&*data, b"\x7FA\xC2\xA9\0");
+}
+
+#[test]
+fn encode_test() {
+	let input = "a\0\u{1F600}";
+
+	let data = EncodeOptions::new().encode(input);
+	assert_eq!(&*data, b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80");
+	assert_eq!(EncodeOptions::new(), EncodeOptions::default());
+
+	let data = EncodeOptions::cesu8().encode(input);
+	assert_eq!(&*data, b"a\0\xED\xA0\xBD\xED\xB8\x80");
+
+	let data = EncodeOptions::new().surrogate_pairs(false).encode(input);
+	assert_eq!(&*data, b"a\xC0\x80\xF0\x9F\x98\x80");
+
+	let data = EncodeOptions::new().escape_nul(false).surrogate_pairs(false).encode(input);
+	assert!(matches!(data, Cow::Borrowed(_)));
+}
@@ -0,0 +1,81 @@
+use std::io::{BufRead, ErrorKind as IoErrorKind, Read};
+
+use mutf8::error::{Error, ErrorKind};
+use mutf8::Mutf8Reader;
+
+/// Yields its input a few bytes at a time.
+struct Trickle<'a> {
+	input: &'a [u8],
+	size: usize,
+}
+
+impl Read for Trickle<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let len = self.input.len().min(self.size).min(buf.len());
+		buf[..len].copy_from_slice(&self.input[..len]);
+		self.input = &self.input[len..];
+		Ok(len)
+	}
+}
+
+const INPUT: &[u8] = b"java/lang/\xC0\x80\xC3\xA9\xE2\x82\xAC\xED\xA0\xBD\xED\xB8\x80 String";
+
+#[test]
+fn read_test() {
+	for size in 1..=8 {
+		for capacity in 1..=8 {
+			let mut reader = Mutf8Reader::with_capacity(capacity, Trickle { input: INPUT, size });
+			let mut output = String::new();
+			reader.read_to_string(&mut output).unwrap();
+			assert_eq!(output, "java/lang/\0\u{E9}\u{20AC}\u{1F600} String");
+		}
+	}
+}
+
+#[test]
+fn buf_read_test() {
+	let input = b"first\xC0\x80\nsecond\n\xED\xA0\xBD\xED\xB8\x80";
+	let reader = Mutf8Reader::with_capacity(3, &input[..]);
+	let lines: Vec<String> = reader.lines().map(|line| line.unwrap()).collect();
+	assert_eq!(lines, ["first\0", "second", "\u{1F600}"]);
+}
+
+#[test]
+fn error_test() {
+	let mut reader = Mutf8Reader::with_capacity(4, &b"abcdef\xFFgh"[..]);
+	let mut output = Vec::new();
+	let error = reader.read_to_end(&mut output).unwrap_err();
+	assert_eq!(output, b"abcdef");
+	assert_eq!(error.kind(), IoErrorKind::InvalidData);
+
+	let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+	assert_eq!(error.kind(), &ErrorKind::InvalidByte);
+	assert_eq!(error.valid_up_to(), 6);
+
+	// The error sticks.
+	assert!(reader.read(&mut [0; 4]).is_err());
+}
+
+#[test]
+fn truncated_test() {
+	let mut reader = Mutf8Reader::new(&b"ab\xED\xA0\xBD\xED"[..]);
+	let mut output = String::new();
+	let error = reader.read_to_string(&mut output).unwrap_err();
+	assert_eq!(error.kind(), IoErrorKind::InvalidData);
+	assert_eq!(reader.fill_buf().unwrap_err().kind(), IoErrorKind::InvalidData);
+}
+
+#[test]
+fn error_kind_test() {
+	// An incomplete sequence followed by another lead byte isn't the end of the input.
+	let input = b"ab\xE2\x82\xE0\x80";
+	for capacity in 1..=input.len() {
+		let mut reader = Mutf8Reader::with_capacity(capacity, &input[..]);
+		let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+		let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+		assert_eq!(error.kind(), &ErrorKind::ExpectedContinuation);
+		assert_eq!(error.valid_up_to(), 2);
+		assert!(error.error_len().is_some());
+		assert_eq!(*error, mutf8::mutf8_to_utf8(input).unwrap_err());
+	}
+}
@@ -0,0 +1,45 @@
+use mutf8::error::{ErrorKind, Expected};
+use mutf8::{sequences, Sequence};
+
+#[test]
+fn sequences_test() {
+	let input = b"a\xC0\x80\xC3\xA9\xE2\x82\xAC\xED\xA0\xBD\xED\xB8\x80\xED\xA0\xBD";
+	let items: Vec<_> = sequences(input).collect();
+	assert_eq!(items, [
+		(0..1, Sequence::Ascii(b'a')),
+		(1..3, Sequence::EncodedNul),
+		(3..5, Sequence::TwoByte('\u{E9}')),
+		(5..8, Sequence::ThreeByte('\u{20AC}')),
+		(8..14, Sequence::SurrogatePair('\u{1F600}')),
+		(14..17, Sequence::LoneSurrogate(0xD83D)),
+	]);
+}
+
+#[test]
+fn invalid_test() {
+	let input = b"a\0\xC1\xBF\xF0\x9F\x98\x80\xE2\x82";
+	let items: Vec<_> = sequences(input).collect();
+	assert_eq!(items, [
+		(0..1, Sequence::Ascii(b'a')),
+		(1..2, Sequence::Invalid { offset: 1, bytes: b"\0", kind: ErrorKind::Nul }),
+		(2..3, Sequence::Invalid { offset: 2, bytes: b"\xC1", kind: ErrorKind::Overlong }),
+		(3..4, Sequence::Invalid { offset: 3, bytes: b"\xBF", kind: ErrorKind::ContinuationByte }),
+		(4..5, Sequence::Invalid { offset: 4, bytes: b"\xF0", kind: ErrorKind::FourByte }),
+		(5..6, Sequence::Invalid { offset: 5, bytes: b"\x9F", kind: ErrorKind::ContinuationByte }),
+		(6..7, Sequence::Invalid { offset: 6, bytes: b"\x98", kind: ErrorKind::ContinuationByte }),
+		(7..8, Sequence::Invalid { offset: 7, bytes: b"\x80", kind: ErrorKind::ContinuationByte }),
+		(8..10, Sequence::Invalid { offset: 8, bytes: b"\xE2\x82", kind: ErrorKind::UnexpectedEnd { expected: Expected::ThreeByte } }),
+	]);
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn mstr_test() {
+	use mutf8::mstr;
+
+	let data = mstr::from_mutf8(b"java/lang/\xC0\x80").unwrap();
+	let ranges: Vec<_> = data.sequences().map(|(range, _)| range).collect();
+	assert_eq!(ranges.len(), 11);
+	assert_eq!(ranges.last(), Some(&(10..12)));
+	assert!(data.sequences().all(|(_, sequence)| !matches!(sequence, Sequence::Invalid { .. })));
+}
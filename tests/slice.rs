@@ -0,0 +1,64 @@
+#![cfg(feature = "use-structs")]
+
+use mutf8::mstr;
+
+// "a", nul, "é", a surrogate pair, and "b".
+const DATA: &[u8] = b"a\xC0\x80\xC3\xA9\xED\xA0\xBD\xED\xB8\x80b";
+
+#[test]
+fn char_boundary_test() {
+	let data = mstr::from_mutf8(DATA).unwrap();
+
+	let boundaries: Vec<usize> = (0..=data.len() + 1).filter(|&index| data.is_char_boundary(index)).collect();
+	assert_eq!(boundaries, [0, 1, 3, 5, 11, 12]);
+
+	assert_eq!(data.floor_char_boundary(8), 5);
+	assert_eq!(data.ceil_char_boundary(8), 11);
+	assert_eq!(data.floor_char_boundary(3), 3);
+	assert_eq!(data.ceil_char_boundary(100), 12);
+
+	// Lone surrogates are their own characters.
+	let data = mstr::from_mutf8(b"\xED\xB8\x80\xED\xB8\x80").unwrap();
+	assert!(data.is_char_boundary(3));
+}
+
+#[test]
+fn index_test() {
+	let data = mstr::from_mutf8(DATA).unwrap();
+
+	assert_eq!(data[1..5].to_str(), "\0\u{E9}");
+	assert_eq!(data[5..].to_str(), "\u{1F600}b");
+	assert_eq!(data[..=0].to_str(), "a");
+	assert_eq!(data[5..=10].to_str(), "\u{1F600}");
+	assert_eq!(data.get(..), Some(data));
+	assert_eq!(data[0], b'a');
+}
+
+#[test]
+fn get_test() {
+	let data = mstr::from_mutf8(DATA).unwrap();
+
+	assert_eq!(data.get(3..5).map(|data| data.to_str()), Some("\u{E9}".into()));
+	assert!(data.get(2..5).is_none());
+	assert!(data.get(5..8).is_none());
+	assert!(data.get(5..13).is_none());
+	let (start, end) = (5, 3);
+	assert!(data.get(start..end).is_none());
+	assert!(data.get(..=usize::MAX).is_none());
+	assert_eq!(data.get(11), Some(&b'b'));
+	assert_eq!(data.get(12), None);
+}
+
+#[test]
+#[should_panic(expected = "not a char boundary")]
+fn split_pair_test() {
+	let data = mstr::from_mutf8(DATA).unwrap();
+	let _ = &data[..8];
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn out_of_bounds_test() {
+	let data = mstr::from_mutf8(DATA).unwrap();
+	let _ = &data[1..20];
+}
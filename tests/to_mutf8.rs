@@ -1,5 +1,5 @@
-use mutf8::mutf8_to_utf8;
-use mutf8::utf8_to_mutf8;
+use mutf8::error::ErrorKind;
+use mutf8::{encode, utf8_to_mutf8};
 
 macro_rules! assert_owned {
 	($var:ident, $msg:expr) => {{
@@ -42,3 +42,32 @@ fn nul_test() {
 		"Data not owned. [A nul byte needs two bytes in mutf8]"
 	);
 }
+
+#[test]
+fn pair_test() {
+	let data = utf8_to_mutf8("\u{1F600}".as_bytes())
+		.expect("invalid input");
+	assert_eq!(&*data, b"\xED\xA0\xBD\xED\xB8\x80");
+}
+
+#[test]
+fn invalid_utf8_test() {
+	let error = utf8_to_mutf8(b"ab\x80cd").unwrap_err();
+	assert!(matches!(error.kind(), ErrorKind::InvalidUtf8(_)));
+	assert_eq!(error.valid_up_to(), 2);
+	assert!(utf8_to_mutf8(b"\xF8\x88\x80\x80\x80").is_err());
+	assert!(utf8_to_mutf8(b"\xED\xA0\x80").is_err());
+	assert!(utf8_to_mutf8(b"\xE2\x82").is_err());
+}
+
+#[test]
+fn encode_test() {
+	let data = encode("value");
+	assert_borrowed!(
+		data,
+		"Data not borrowed. [It's just pure ascii which uses the same encoding as utf8]"
+	);
+
+	let data = encode("a\0\u{E9}\u{1F600}");
+	assert_eq!(&*data, b"a\xC0\x80\xC3\xA9\xED\xA0\xBD\xED\xB8\x80");
+}
@@ -0,0 +1,113 @@
+use mutf8::error::{ErrorKind, Expected};
+use mutf8::mutf8_to_utf8;
+
+macro_rules! assert_invalid {
+	($input:expr, $invalid:expr) => {{
+		let error = mutf8_to_utf8($input).expect_err("valid input");
+		assert_eq!(error.kind(), &$invalid);
+	}};
+}
+
+#[test]
+fn ascii_test() {
+	let data = mutf8_to_utf8(b"value")
+		.expect("invalid input");
+	assert_eq!(&*data, b"value");
+}
+
+#[test]
+fn nul_test() {
+	let data = mutf8_to_utf8(b"a\xC0\x80b")
+		.expect("invalid input");
+	assert_eq!(&*data, b"a\0b");
+}
+
+#[test]
+fn pair_test() {
+	let data = mutf8_to_utf8(b"\xED\xA0\xBD\xED\xB8\x80")
+		.expect("invalid input");
+	assert_eq!(&*data, "\u{1F600}".as_bytes());
+}
+
+#[test]
+fn lone_surrogate_test() {
+	assert_invalid!(b"\xED\xA0\xBDa", ErrorKind::LoneSurrogate);
+	assert_invalid!(b"a\xED\xB8\x80", ErrorKind::LoneSurrogate);
+}
+
+#[test]
+fn invalid_test() {
+	assert_invalid!(b"a\0b", ErrorKind::Nul);
+	assert_invalid!(b"a\x80b", ErrorKind::ContinuationByte);
+	assert_invalid!(b"\xC3a", ErrorKind::ExpectedContinuation);
+	assert_invalid!(b"\xE2\x82a", ErrorKind::ExpectedContinuation);
+	assert_invalid!(b"\xC1\xBF", ErrorKind::Overlong);
+	assert_invalid!(b"\xE0\x80\x80", ErrorKind::Overlong);
+	assert_invalid!(b"\xF0\x9F\x98\x80", ErrorKind::FourByte);
+	assert_invalid!(b"\xFF", ErrorKind::InvalidByte);
+}
+
+#[test]
+fn end_of_input_test() {
+	let error = mutf8_to_utf8(b"\xC3").unwrap_err();
+	assert_eq!(error.kind(), &ErrorKind::UnexpectedEnd { expected: Expected::TwoByte });
+	assert_eq!(error.error_len(), None);
+
+	let error = mutf8_to_utf8(b"\xE2\x82").unwrap_err();
+	assert_eq!(error.kind(), &ErrorKind::UnexpectedEnd { expected: Expected::ThreeByte });
+	assert_eq!(error.error_len(), None);
+}
+
+#[test]
+fn valid_up_to_test() {
+	let input = b"abc\xC0\x80\xED\xA0\xBD\xED\xB8\x80\xFFdef";
+	let error = mutf8_to_utf8(input).unwrap_err();
+	assert_eq!(error.valid_up_to(), 11);
+	assert!(mutf8_to_utf8(&input[..error.valid_up_to()]).is_ok());
+
+	assert_eq!(mutf8_to_utf8(b"ab\xE2\x82").unwrap_err().valid_up_to(), 2);
+	assert_eq!(mutf8_to_utf8(b"ab\0").unwrap_err().valid_up_to(), 2);
+}
+
+#[test]
+fn decode_test() {
+	use mutf8::decode;
+	use std::borrow::Cow;
+
+	let data = decode(b"value \xC3\xA9").expect("invalid input");
+	assert!(matches!(data, Cow::Borrowed("value \u{E9}")));
+
+	let data = decode(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80").expect("invalid input");
+	assert!(matches!(data, Cow::Owned(_)));
+	assert_eq!(data, "a\0\u{1F600}");
+
+	assert_eq!(decode(b"a\xED\xA0\xBD").unwrap_err().kind(), &ErrorKind::LoneSurrogate);
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn mstr_test() {
+	use mutf8::mstr;
+
+	let data = mstr::from_mutf8(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80").unwrap();
+	assert_eq!(data.to_utf8().expect("invalid input"), "a\0\u{1F600}");
+	assert_eq!(data.to_owned().into_string().expect("invalid input"), "a\0\u{1F600}");
+
+	let error = mstr::from_mutf8(b"ab\xED\xA0\xBD").unwrap().to_utf8().unwrap_err();
+	assert_eq!(error.valid_up_to(), 2);
+}
+
+#[cfg(feature = "use-structs")]
+#[test]
+fn from_mutf8_test() {
+	use mutf8::error::ErrorKind;
+	use mutf8::{mstr, MString};
+
+	assert!(mstr::from_mutf8(b"a\xC0\x80\xED\xA0\xBD").is_ok());
+	assert_eq!(mstr::from_mutf8(b"a\0").unwrap_err().kind(), &ErrorKind::Nul);
+	assert_eq!(mstr::from_mutf8(b"a\xFF").unwrap_err().valid_up_to(), 1);
+	assert_eq!(MString::from_mutf8(&b"\xF0\x9F\x98\x80"[..]).unwrap_err().kind(), &ErrorKind::FourByte);
+
+	let data = unsafe { mstr::from_mutf8_unchecked(b"value") };
+	assert_eq!(data.to_str(), "value");
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "use-structs")]
+
+use mutf8::{mstr, MString};
+
+#[test]
+fn encode_utf16_test() {
+	let data = mstr::from_mutf8(b"a\xC0\x80\xC3\xA9\xED\xA0\xBD\xED\xB8\x80\xED\xA0\xBD").unwrap();
+
+	let units: Vec<u16> = data.encode_utf16().collect();
+	assert_eq!(units, [0x61, 0x00, 0xE9, 0xD83D, 0xDE00, 0xD83D]);
+
+	let units: Vec<u16> = data.encode_utf16().rev().collect();
+	assert_eq!(units, [0xD83D, 0xDE00, 0xD83D, 0xE9, 0x00, 0x61]);
+
+	let units: Vec<u16> = "a\0\u{E9}\u{1F600}".encode_utf16().collect();
+	let data = MString::from_utf8(b"a\0\xC3\xA9\xF0\x9F\x98\x80").unwrap();
+	assert_eq!(data.encode_utf16().collect::<Vec<u16>>(), units);
+}
+
+#[test]
+fn from_utf16_test() {
+	let data = MString::from_utf16(&[0x61, 0x00, 0xE9, 0x20AC, 0xD83D, 0xDE00]);
+	assert_eq!(data.as_mutf8_bytes(), b"a\xC0\x80\xC3\xA9\xE2\x82\xAC\xED\xA0\xBD\xED\xB8\x80");
+	assert_eq!(data.to_str(), "a\0\u{E9}\u{20AC}\u{1F600}");
+
+	// Lone surrogates are kept, so any sequence survives the round trip.
+	let units = [0xDE00, 0x61, 0xD83D, 0xFFFF];
+	let data = MString::from_utf16(&units);
+	assert_eq!(data.as_mutf8_bytes(), b"\xED\xB8\x80a\xED\xA0\xBD\xEF\xBF\xBF");
+	assert_eq!(data.encode_utf16().collect::<Vec<u16>>(), units);
+	assert!(mstr::from_mutf8(data.as_mutf8_bytes()).is_ok());
+
+	assert!(MString::from_utf16(&[]).is_empty());
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "use-structs")]
+
+use mutf8::mstr;
+
+#[test]
+fn len_test() {
+	// "a\0é😀" followed by a lone surrogate.
+	let data = mstr::from_mutf8(b"a\xC0\x80\xC3\xA9\xED\xA0\xBD\xED\xB8\x80\xED\xA0\xBD").unwrap();
+	assert_eq!(data.utf16_len(), 6);
+	assert_eq!(data.utf16_len(), data.encode_utf16().count());
+	assert_eq!(data.char_count(), 5);
+
+	let data = mstr::from_mutf8(b"").unwrap();
+	assert_eq!(data.utf16_len(), 0);
+	assert_eq!(data.char_count(), 0);
+}
+
+#[test]
+fn index_test() {
+	let data = mstr::from_mutf8(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80b").unwrap();
+
+	let offsets: Vec<Option<usize>> = (0..7).map(|index| data.utf16_index_to_offset(index)).collect();
+	assert_eq!(offsets, [Some(0), Some(1), Some(3), Some(6), Some(9), Some(10), None]);
+
+	let indices: Vec<Option<usize>> = (0..12).map(|offset| data.offset_to_utf16_index(offset)).collect();
+	assert_eq!(indices, [Some(0), Some(1), None, Some(2), None, None, Some(3), None, None, Some(4), Some(5), None]);
+}
+
+#[test]
+fn substring_utf16_test() {
+	let data = mstr::from_mutf8(b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80b").unwrap();
+
+	assert_eq!(data.substring_utf16(1, 4).as_bytes(), b"\xC0\x80\xED\xA0\xBD\xED\xB8\x80");
+	assert_eq!(data.substring_utf16(0, 5).as_bytes(), data.as_bytes());
+	assert!(data.substring_utf16(5, 5).is_empty());
+
+	// Splitting a surrogate pair leaves a lone surrogate behind.
+	assert_eq!(data.substring_utf16(3, 5).as_bytes(), b"\xED\xB8\x80b");
+	assert_eq!(data.substring_utf16(3, 5).to_str(), "\u{FFFD}b");
+}
+
+#[test]
+#[should_panic]
+fn substring_utf16_out_of_range_test() {
+	let data = mstr::from_mutf8(b"ab").unwrap();
+	data.substring_utf16(1, 3);
+}
+
+#[test]
+#[should_panic]
+fn substring_utf16_reversed_test() {
+	let data = mstr::from_mutf8(b"ab").unwrap();
+	data.substring_utf16(2, 1);
+}
@@ -0,0 +1,91 @@
+use std::io::{ErrorKind as IoErrorKind, Write};
+
+use mutf8::error::{Error, ErrorKind, Expected};
+use mutf8::Mutf8Writer;
+
+const INPUT: &str = "java/lang/\0\u{E9}\u{20AC}\u{1F600} String";
+const OUTPUT: &[u8] = b"java/lang/\xC0\x80\xC3\xA9\xE2\x82\xAC\xED\xA0\xBD\xED\xB8\x80 String";
+
+#[test]
+fn write_test() {
+	for size in 1..=8 {
+		for capacity in 0..=8 {
+			let mut writer = Mutf8Writer::with_capacity(capacity, Vec::new());
+			for chunk in INPUT.as_bytes().chunks(size) {
+				writer.write_all(chunk).unwrap();
+			}
+			assert_eq!(writer.finish().unwrap(), OUTPUT);
+		}
+	}
+}
+
+#[test]
+fn format_test() {
+	let mut writer = Mutf8Writer::new(Vec::new());
+	let (package, name, c) = ("java", "lang", '\u{1F600}');
+	write!(writer, "{}/{}", package, name).unwrap();
+	writeln!(writer, "{}", c).unwrap();
+	writer.flush().unwrap();
+	assert_eq!(writer.get_ref(), b"java/lang\xED\xA0\xBD\xED\xB8\x80\n");
+
+	// Dropping the writer writes out the buffer.
+	let mut output = Vec::new();
+	{
+		let mut writer = Mutf8Writer::new(&mut output);
+		writer.write_all(b"a\0").unwrap();
+	}
+	assert_eq!(output, b"a\xC0\x80");
+}
+
+#[test]
+fn error_test() {
+	let mut writer = Mutf8Writer::new(Vec::new());
+	assert_eq!(writer.write(b"ab\xFFcd").unwrap(), 2);
+
+	let error = writer.write(b"\xFFcd").unwrap_err();
+	assert_eq!(error.kind(), IoErrorKind::InvalidData);
+	let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+	assert_eq!(error.kind(), &ErrorKind::InvalidSequence);
+	assert_eq!(error.valid_up_to(), 2);
+
+	let mut writer = Mutf8Writer::new(Vec::new());
+	let error = writer.write_all(b"abc\xE2\x82A").unwrap_err();
+	let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+	assert_eq!(error.valid_up_to(), 3);
+	assert_eq!(error.error_len(), Some(2));
+
+	// The offset is into everything written, not just the write that failed.
+	let mut writer = Mutf8Writer::new(Vec::new());
+	writer.write_all(b"abc").unwrap();
+	writer.write_all(b"def").unwrap();
+	let error = writer.write(b"\xFFcd").unwrap_err();
+	let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+	assert_eq!(error.valid_up_to(), 6);
+	let message = error.to_string();
+	assert!(message.contains("at byte 6"), "{}", message);
+	assert!(!message.contains("index 0"), "{}", message);
+
+	let mut writer = Mutf8Writer::new(Vec::new());
+	writer.write_all(b"abc\xE2").unwrap();
+	let error = writer.write(b"A").unwrap_err();
+	let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+	assert_eq!(error.valid_up_to(), 3);
+	assert!(error.to_string().contains("at byte 3"), "{}", error);
+}
+
+#[test]
+fn finish_test() {
+	let mut writer = Mutf8Writer::new(Vec::new());
+	writer.write_all(b"ab\xF0\x9F").unwrap();
+	writer.flush().unwrap();
+	assert_eq!(writer.get_ref(), b"ab");
+
+	let error = writer.finish().unwrap_err();
+	let error = error.into_inner().unwrap().downcast::<Error>().unwrap();
+	assert_eq!(error.kind(), &ErrorKind::UnexpectedEnd {
+		expected: Expected::FourByte,
+	});
+	assert_eq!(error.valid_up_to(), 2);
+	assert_eq!(error.error_len(), None);
+	assert!(error.to_string().contains("at byte 2"), "{}", error);
+}
@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+
+use mutf8::error::ErrorKind;
+use mutf8::{mutf8_to_wtf8, wtf8_to_mutf8};
+
+#[test]
+fn decode_test() {
+	let data = mutf8_to_wtf8(b"a\xED\xA0\xBDb").unwrap();
+	assert!(matches!(data, Cow::Borrowed(_)));
+
+	let data = mutf8_to_wtf8(b"\xC0\x80\xED\xA0\xBD\xED\xB8\x80\xED\xB8\x80").unwrap();
+	assert_eq!(&*data, b"\0\xF0\x9F\x98\x80\xED\xB8\x80");
+
+	assert_eq!(mutf8_to_wtf8(b"\0").unwrap_err().kind(), &ErrorKind::Nul);
+}
+
+#[test]
+fn encode_test() {
+	let data = wtf8_to_mutf8(b"a\xED\xA0\xBDb").unwrap();
+	assert!(matches!(data, Cow::Borrowed(_)));
+
+	let data = wtf8_to_mutf8(b"\0\xF0\x9F\x98\x80\xED\xB8\x80").unwrap();
+	assert_eq!(&*data, b"\xC0\x80\xED\xA0\xBD\xED\xB8\x80\xED\xB8\x80");
+
+	assert_eq!(wtf8_to_mutf8(b"\xC0\x80").unwrap_err().kind(), &ErrorKind::Overlong);
+	assert_eq!(wtf8_to_mutf8(b"ab\xFF").unwrap_err().valid_up_to(), 2);
+}
+
+#[test]
+fn round_trip_test() {
+	let input: &[u8] = b"\xED\xB8\x80a\xC0\x80\xED\xA0\xBD\xED\xB8\x80\xED\xA0\xBD";
+	let wtf8 = mutf8_to_wtf8(input).unwrap();
+	let mutf8 = wtf8_to_mutf8(&wtf8).unwrap();
+	assert_eq!(&*mutf8, input);
+}